@@ -0,0 +1,27 @@
+-- Permisos granulares por rol
+CREATE TABLE IF NOT EXISTS permiso (
+    id_permiso SERIAL PRIMARY KEY,
+    nombre VARCHAR(100) NOT NULL UNIQUE,
+    descripcion VARCHAR(255)
+);
+
+CREATE TABLE IF NOT EXISTS rol_permiso (
+    id_rol INTEGER NOT NULL REFERENCES rol (id_rol) ON DELETE CASCADE,
+    id_permiso INTEGER NOT NULL REFERENCES permiso (id_permiso) ON DELETE CASCADE,
+    PRIMARY KEY (id_rol, id_permiso)
+);
+
+INSERT INTO permiso (nombre, descripcion) VALUES
+    ('consultar_elector', 'Consultar datos del registro electoral'),
+    ('ver_miembros_mesa', 'Ver datos de miembro de mesa del elector'),
+    ('exportar', 'Exportar listados'),
+    ('administrar_usuarios', 'Administrar usuarios, roles y permisos')
+ON CONFLICT (nombre) DO NOTHING;
+
+-- El rol administrador recibe todos los permisos iniciales
+INSERT INTO rol_permiso (id_rol, id_permiso)
+SELECT r.id_rol, p.id_permiso
+FROM rol r
+CROSS JOIN permiso p
+WHERE UPPER(TRIM(r.nombre)) = 'ADMINISTRADOR'
+ON CONFLICT DO NOTHING;
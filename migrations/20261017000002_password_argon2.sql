@@ -0,0 +1,2 @@
+-- Los hash Argon2id en formato PHC ocupan ~97 caracteres (SHA-256 hex ocupaba 64)
+ALTER TABLE usuario ALTER COLUMN password TYPE VARCHAR(255);
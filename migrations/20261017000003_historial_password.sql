@@ -0,0 +1,9 @@
+-- Contraseñas anteriores (hash) para impedir su reutilización
+CREATE TABLE IF NOT EXISTS historial_password (
+    id SERIAL PRIMARY KEY,
+    id_usuario INTEGER NOT NULL REFERENCES usuario (id) ON DELETE CASCADE,
+    password VARCHAR(255) NOT NULL,
+    fecha TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS historial_password_usuario_idx ON historial_password (id_usuario, fecha DESC);
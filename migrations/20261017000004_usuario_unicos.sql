@@ -0,0 +1,3 @@
+-- Login y cédula únicos. Si existen duplicados previos deben depurarse antes de aplicar.
+CREATE UNIQUE INDEX IF NOT EXISTS usuario_login_key ON usuario (login);
+CREATE UNIQUE INDEX IF NOT EXISTS usuario_cedula_key ON usuario (cedula);
@@ -0,0 +1,3 @@
+-- Descripción de rol y nombres únicos (la autorización compara rol.nombre)
+ALTER TABLE rol ADD COLUMN IF NOT EXISTS descripcion VARCHAR(255);
+CREATE UNIQUE INDEX IF NOT EXISTS rol_nombre_key ON rol (UPPER(TRIM(nombre)));
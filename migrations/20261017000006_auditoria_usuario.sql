@@ -0,0 +1,37 @@
+-- Bitácora de cambios administrativos (solo inserción)
+CREATE TABLE IF NOT EXISTS auditoria_usuario (
+    id BIGSERIAL PRIMARY KEY,
+    id_actor INTEGER NOT NULL,
+    entidad VARCHAR(30) NOT NULL,   -- usuario | rol | permiso
+    id_entidad INTEGER,
+    accion VARCHAR(50) NOT NULL,
+    antes JSONB,
+    despues JSONB,
+    ip VARCHAR(64),
+    fecha TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS auditoria_usuario_entidad_idx ON auditoria_usuario (entidad, id_entidad, fecha DESC);
+CREATE INDEX IF NOT EXISTS auditoria_usuario_actor_idx ON auditoria_usuario (id_actor, fecha DESC);
+
+CREATE OR REPLACE FUNCTION auditoria_solo_insercion() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'La tabla % es de solo inserción', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS auditoria_usuario_inmutable ON auditoria_usuario;
+CREATE TRIGGER auditoria_usuario_inmutable
+    BEFORE UPDATE OR DELETE ON auditoria_usuario
+    FOR EACH ROW EXECUTE FUNCTION auditoria_solo_insercion();
+
+INSERT INTO permiso (nombre, descripcion) VALUES
+    ('ver_auditoria', 'Consultar bitácoras de auditoría')
+ON CONFLICT (nombre) DO NOTHING;
+
+INSERT INTO rol_permiso (id_rol, id_permiso)
+SELECT r.id_rol, p.id_permiso
+FROM rol r
+CROSS JOIN permiso p
+WHERE UPPER(TRIM(r.nombre)) = 'ADMINISTRADOR' AND p.nombre = 'ver_auditoria'
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,22 @@
+-- Bitácora de consultas de datos de ciudadanos (solo inserción)
+CREATE TABLE IF NOT EXISTS auditoria_consulta (
+    id BIGSERIAL PRIMARY KEY,
+    id_usuario INTEGER NOT NULL,
+    endpoint VARCHAR(50) NOT NULL,  -- get_elector | get_electores | get_movimientos_re | get_usuario_by_ac
+    nacionalidad VARCHAR(10),
+    cedula BIGINT,
+    filtros JSONB,
+    resultados INTEGER NOT NULL DEFAULT 0,
+    cedulas_resultado BIGINT[],     -- cédulas devueltas por las búsquedas por filtros
+    ip VARCHAR(64),
+    fecha TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS auditoria_consulta_cedula_idx ON auditoria_consulta (cedula, fecha DESC);
+CREATE INDEX IF NOT EXISTS auditoria_consulta_usuario_idx ON auditoria_consulta (id_usuario, fecha DESC);
+CREATE INDEX IF NOT EXISTS auditoria_consulta_cedulas_idx ON auditoria_consulta USING GIN (cedulas_resultado);
+
+DROP TRIGGER IF EXISTS auditoria_consulta_inmutable ON auditoria_consulta;
+CREATE TRIGGER auditoria_consulta_inmutable
+    BEFORE UPDATE OR DELETE ON auditoria_consulta
+    FOR EACH ROW EXECUTE FUNCTION auditoria_solo_insercion();
@@ -0,0 +1,17 @@
+-- Catálogo de motivos de consulta (obligatorio en get_elector y get_movimientos_re)
+CREATE TABLE IF NOT EXISTS motivo_consulta (
+    id_motivo SERIAL PRIMARY KEY,
+    codigo VARCHAR(30) NOT NULL UNIQUE,
+    descripcion VARCHAR(200) NOT NULL,
+    activo INTEGER NOT NULL DEFAULT 1
+);
+
+INSERT INTO motivo_consulta (codigo, descripcion) VALUES
+    ('ATENCION_CIUDADANO', 'Atención al ciudadano'),
+    ('SOLICITUD_OFICIAL', 'Solicitud de organismo oficial'),
+    ('ACTUALIZACION_DATOS', 'Verificación para actualización de datos'),
+    ('AUDITORIA_INTERNA', 'Auditoría interna')
+ON CONFLICT (codigo) DO NOTHING;
+
+ALTER TABLE auditoria_consulta
+    ADD COLUMN IF NOT EXISTS id_motivo INTEGER REFERENCES motivo_consulta (id_motivo);
@@ -0,0 +1,9 @@
+-- Antes de los permisos cualquier rol podía consultar electores (con datos de miembro de mesa).
+-- Los roles existentes que aún no tienen permisos conservan ese acceso.
+INSERT INTO rol_permiso (id_rol, id_permiso)
+SELECT r.id_rol, p.id_permiso
+FROM rol r
+CROSS JOIN permiso p
+WHERE p.nombre IN ('consultar_elector', 'ver_miembros_mesa')
+  AND NOT EXISTS (SELECT 1 FROM rol_permiso rp WHERE rp.id_rol = r.id_rol)
+ON CONFLICT DO NOTHING;
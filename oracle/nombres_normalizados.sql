@@ -0,0 +1,28 @@
+-- Nombres normalizados para la búsqueda de electores (GET /electores).
+--
+-- V_RE_ACTUAL_CVA es una vista y no admite índices: la normalización se guarda como
+-- columnas virtuales en la tabla de la que la vista toma los nombres (RE.AC) y se
+-- indexa ahí. Debe aplicarse antes de desplegar la versión que las usa.
+--
+-- La expresión es la misma que texto::normalizar en Rust (espacios colapsados, sin
+-- espacios en los extremos, mayúsculas y sin acentos); la prueba
+-- registro_oracle::tests::ddl_usa_la_normalizacion_de_rust falla si dejan de coincidir.
+-- Requiere que la base use AL32UTF8 (los literales de TRANSLATE llevan acentos).
+
+ALTER TABLE RE.AC ADD (
+  PRIMER_NOMBRE_NORM AS (TRANSLATE(UPPER(TRIM(REGEXP_REPLACE(PRIMER_NOMBRE, '[[:space:]]+', ' '))), 'ÁÀÄÂÃÉÈËÊÍÌÏÎÓÒÖÔÕÚÙÜÛÑÇ', 'AAAAAEEEEIIIIOOOOOUUUUNC')) VIRTUAL,
+  SEGUNDO_NOMBRE_NORM AS (TRANSLATE(UPPER(TRIM(REGEXP_REPLACE(SEGUNDO_NOMBRE, '[[:space:]]+', ' '))), 'ÁÀÄÂÃÉÈËÊÍÌÏÎÓÒÖÔÕÚÙÜÛÑÇ', 'AAAAAEEEEIIIIOOOOOUUUUNC')) VIRTUAL,
+  PRIMER_APELLIDO_NORM AS (TRANSLATE(UPPER(TRIM(REGEXP_REPLACE(PRIMER_APELLIDO, '[[:space:]]+', ' '))), 'ÁÀÄÂÃÉÈËÊÍÌÏÎÓÒÖÔÕÚÙÜÛÑÇ', 'AAAAAEEEEIIIIOOOOOUUUUNC')) VIRTUAL,
+  SEGUNDO_APELLIDO_NORM AS (TRANSLATE(UPPER(TRIM(REGEXP_REPLACE(SEGUNDO_APELLIDO, '[[:space:]]+', ' '))), 'ÁÀÄÂÃÉÈËÊÍÌÏÎÓÒÖÔÕÚÙÜÛÑÇ', 'AAAAAEEEEIIIIOOOOOUUUUNC')) VIRTUAL
+);
+
+-- Sirven para los modos exacto (=) y prefijo (LIKE 'X%'). El modo contiene
+-- (LIKE '%X%') no puede usar índice y siempre recorre la tabla.
+CREATE INDEX RE.AC_PRIMER_NOMBRE_NORM_IX ON RE.AC (PRIMER_NOMBRE_NORM);
+CREATE INDEX RE.AC_SEGUNDO_NOMBRE_NORM_IX ON RE.AC (SEGUNDO_NOMBRE_NORM);
+CREATE INDEX RE.AC_PRIMER_APELLIDO_NORM_IX ON RE.AC (PRIMER_APELLIDO_NORM);
+CREATE INDEX RE.AC_SEGUNDO_APELLIDO_NORM_IX ON RE.AC (SEGUNDO_APELLIDO_NORM);
+
+-- Por último, recrear V_RE_ACTUAL_CVA (CREATE OR REPLACE VIEW con su definición actual)
+-- agregando a la lista de columnas:
+--   AC.PRIMER_NOMBRE_NORM, AC.SEGUNDO_NOMBRE_NORM, AC.PRIMER_APELLIDO_NORM, AC.SEGUNDO_APELLIDO_NORM
@@ -1,33 +1,69 @@
 #![allow(non_snake_case)]
 
 use actix_cors::Cors;
+use actix_web::middleware::from_fn;
 use actix_web::{web, App, HttpServer};
 use dotenvy::dotenv;
 use sqlx::postgres::PgPool;
 use std::env;
+use std::sync::Arc;
+use modules::control::{ControlAcceso, ControlPostgres};
+use modules::registro::RegistroElectoralRepo;
 
 mod structs {
     use sqlx::postgres::PgPool;
+    use std::sync::Arc;
+    use crate::modules::control::ControlAcceso;
+    use crate::modules::registro::RegistroElectoralRepo;
 
     #[derive(Clone)]
     pub struct AppState {
         pub pool_pg: PgPool,
+        pub registro: Arc<dyn RegistroElectoralRepo>,
+        // Permisos, motivos y bitácora de consultas (ver modules::control)
+        pub control: Arc<dyn ControlAcceso>,
         pub jwt_secret: String,
     }
 }
 
 mod modules {
+    pub mod auth;
     pub mod login;
+    pub mod cuenta;
+    pub mod passwords;
     pub mod re;
     pub mod ac;
     pub mod users;
+    pub mod permisos;
+    pub mod roles;
+    pub mod texto;
+    pub mod importacion;
+    pub mod paginacion;
+    pub mod auditoria;
+    pub mod motivos;
+    pub mod oracle_db;
+    pub mod registro;
+    pub mod registro_oracle;
+    pub mod registro_memoria;
+    pub mod control;
 
     pub use login::get_login;
+    pub use cuenta::cambiar_password;
     pub use re::{get_movimientos_re, get_elector, get_electores}; // ✅ agregado get_elector
-    pub use users::{get_usuarios, crear_usuario, actualizar_usuario, bloquear_usuario, carga_masiva, get_roles};
+    pub use users::{get_usuarios, exportar_usuarios, crear_usuario, actualizar_usuario, bloquear_usuario, carga_masiva};
+    pub use roles::{get_roles, crear_rol, actualizar_rol, eliminar_rol};
     pub use ac::{get_usuario_by_ac}; // ✅ agregado get_usuario_by_ac
+    pub use auditoria::{get_auditoria, get_auditoria_consultas};
+    pub use motivos::{get_motivos_consulta, crear_motivo_consulta, actualizar_motivo_consulta};
+    pub use permisos::{get_permisos, crear_permiso, actualizar_permiso, eliminar_permiso, get_permisos_rol, asignar_permisos_rol};
 }
 
+// Permisos exigidos por ruta (ver modules::auth::require_permisos)
+const CONSULTAR_ELECTOR: &[&str] = &[modules::auth::PERM_CONSULTAR_ELECTOR];
+const ADMINISTRAR_USUARIOS: &[&str] = &[modules::auth::PERM_ADMINISTRAR_USUARIOS];
+const EXPORTAR_USUARIOS: &[&str] = &[modules::auth::PERM_ADMINISTRAR_USUARIOS, modules::auth::PERM_EXPORTAR];
+const VER_AUDITORIA: &[&str] = &[modules::auth::PERM_VER_AUDITORIA];
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
@@ -39,6 +75,18 @@ async fn main() -> std::io::Result<()> {
     let jwt_secret = env::var("JWT_SECRET").expect("Variable JWT_SECRET faltante");
 
     let pool_pg = PgPool::connect(&url_pg).await.expect("Error conectando a BD");
+    let control: Arc<dyn ControlAcceso> = Arc::new(ControlPostgres::new(pool_pg.clone()));
+    // REGISTRO_FIXTURE=archivo.json: registro electoral en memoria, sin Oracle
+    let registro: Arc<dyn RegistroElectoralRepo> = match env::var("REGISTRO_FIXTURE") {
+        Ok(ruta) => Arc::new(
+            modules::registro_memoria::RegistroMemoria::desde_archivo(&ruta).expect("Error cargando REGISTRO_FIXTURE"),
+        ),
+        Err(_) => Arc::new(modules::registro_oracle::RegistroOracle::new(
+            modules::oracle_db::crear_pool()
+                .map(modules::oracle_db::OracleDb::new)
+                .expect("Error configurando Oracle"),
+        )),
+    };
 
     println!("\n🚀 Backend SCORE iniciado");
     println!("========================================");
@@ -62,23 +110,119 @@ async fn main() -> std::io::Result<()> {
             )
             .app_data(web::Data::new(structs::AppState {
                 pool_pg: pool_pg.clone(),
+                registro: registro.clone(),
+                control: control.clone(),
                 jwt_secret: jwt_secret.clone(),
             }))
             .service(
                 web::scope("/api")
                     .route("/login", web::post().to(modules::get_login))
-                    .route(
-                        "/get-movimientos-re/{nacionalidad}/{cedula}",
-                        web::get().to(modules::get_movimientos_re),)
-                    .route("/get_elector", web::get().to(modules::get_elector))
-                    .route("/get_electores", web::get().to(modules::get_electores))
-                    .route("/usuarios", web::get().to(modules::get_usuarios))
-                    .route("/usuarios", web::post().to(modules::crear_usuario))
-                    .route("/usuarios/{id}", web::put().to(modules::actualizar_usuario))
-                    .route("/usuarios/{id}/bloquear", web::put().to(modules::bloquear_usuario))
-                    .route("/usuarios/carga-masiva", web::post().to(modules::carga_masiva))
-                    .route("/get_usuario_by_ac/{nacionalidad}/{cedula}", web::get().to(modules::get_usuario_by_ac))
-                    .route("/roles", web::get().to(modules::get_roles)),
+                    .service(
+                        web::scope("")
+                            .wrap(from_fn(modules::auth::require_auth))
+                            .route("/me/password", web::put().to(modules::cambiar_password))
+                            .service(
+                                web::resource("/get-movimientos-re/{nacionalidad}/{cedula}")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, CONSULTAR_ELECTOR)))
+                                    .route(web::get().to(modules::get_movimientos_re)),
+                            )
+                            .service(
+                                web::resource("/get_elector")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, CONSULTAR_ELECTOR)))
+                                    .route(web::get().to(modules::get_elector)),
+                            )
+                            .service(
+                                web::resource("/get_electores")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, CONSULTAR_ELECTOR)))
+                                    .route(web::get().to(modules::get_electores)),
+                            )
+                            // El listado de motivos es para cualquier usuario autenticado; el alta no
+                            .service(
+                                web::resource("/motivos-consulta")
+                                    .route(web::get().to(modules::get_motivos_consulta))
+                                    .route(
+                                        web::post()
+                                            .to(modules::crear_motivo_consulta)
+                                            .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS))),
+                                    ),
+                            )
+                            .service(
+                                web::resource("/motivos-consulta/{id}")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::put().to(modules::actualizar_motivo_consulta)),
+                            )
+                            .service(
+                                web::resource("/usuarios")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::get().to(modules::get_usuarios))
+                                    .route(web::post().to(modules::crear_usuario)),
+                            )
+                            .service(
+                                web::resource("/usuarios/exportar")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, EXPORTAR_USUARIOS)))
+                                    .route(web::get().to(modules::exportar_usuarios)),
+                            )
+                            .service(
+                                web::resource("/usuarios/carga-masiva")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::post().to(modules::carga_masiva)),
+                            )
+                            .service(
+                                web::resource("/usuarios/{id}")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::put().to(modules::actualizar_usuario)),
+                            )
+                            .service(
+                                web::resource("/usuarios/{id}/bloquear")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::put().to(modules::bloquear_usuario)),
+                            )
+                            .service(
+                                web::resource("/get_usuario_by_ac/{nacionalidad}/{cedula}")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::get().to(modules::get_usuario_by_ac)),
+                            )
+                            .service(
+                                web::resource("/roles")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::get().to(modules::get_roles))
+                                    .route(web::post().to(modules::crear_rol)),
+                            )
+                            .service(
+                                web::resource("/roles/{id}")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::put().to(modules::actualizar_rol))
+                                    .route(web::delete().to(modules::eliminar_rol)),
+                            )
+                            .service(
+                                web::resource("/roles/{id}/permisos")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::get().to(modules::get_permisos_rol))
+                                    .route(web::put().to(modules::asignar_permisos_rol)),
+                            )
+                            .service(
+                                web::resource("/permisos")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::get().to(modules::get_permisos))
+                                    .route(web::post().to(modules::crear_permiso)),
+                            )
+                            .service(
+                                web::resource("/permisos/{id}")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, ADMINISTRAR_USUARIOS)))
+                                    .route(web::put().to(modules::actualizar_permiso))
+                                    .route(web::delete().to(modules::eliminar_permiso)),
+                            )
+                            .service(
+                                web::resource("/auditoria")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, VER_AUDITORIA)))
+                                    .route(web::get().to(modules::get_auditoria)),
+                            )
+                            .service(
+                                web::resource("/auditoria/consultas")
+                                    .wrap(from_fn(|req, next| modules::auth::require_permisos(req, next, VER_AUDITORIA)))
+                                    .route(web::get().to(modules::get_auditoria_consultas)),
+                            ),
+                    ),
             )
     })
     .bind(("127.0.0.1", 9000))?
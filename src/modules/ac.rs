@@ -1,17 +1,8 @@
-use actix_web::{web, HttpResponse, Error};
-use oracle::Connection;
+use actix_web::{web, HttpRequest, HttpResponse, Error};
 use serde::{Deserialize, Serialize};
-use std::env;
-
-fn oracle_conn() -> Result<Connection, oracle::Error> {
-    let username = env::var("ORACLE_USER").unwrap();
-    let password = env::var("ORACLE_PASS").unwrap();
-    let oracle_ip = env::var("ORACLE_IP").unwrap();
-    let oracle_port = env::var("ORACLE_PORT").unwrap();
-    let oracle_db = env::var("ORACLE_DB").unwrap();
-    let connect_string = format!("//{oracle_ip}:{oracle_port}/{oracle_db}");
-    Connection::connect(username, password, connect_string)
-}
+use crate::modules::auditoria::{self, Consulta, Contexto};
+use crate::modules::auth::{self, AuthUser};
+use crate::structs::AppState;
 
 #[derive(Deserialize, Serialize, Default)]
 pub struct UsuarioAC {
@@ -24,8 +15,13 @@ pub struct UsuarioAC {
 }
 
 pub async fn get_usuario_by_ac(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
     path: web::Path<(String, i64)>,
 ) -> Result<HttpResponse, Error> {
+    auth::require_permiso(&state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await?;
+
     let (nacionalidad_raw, cedula) = path.into_inner();
     let nacionalidad = nacionalidad_raw.trim().to_uppercase();
     
@@ -36,42 +32,55 @@ pub async fn get_usuario_by_ac(
         return Err(actix_web::error::ErrorBadRequest("cedula inválida"));
     }
 
-    let conn = oracle_conn()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error conectando a Oracle: {}", e)))?;
-
-    let sql_persona = r#"
-        SELECT 
-          PRIMER_APELLIDO, 
-          SEGUNDO_APELLIDO, 
-          PRIMER_NOMBRE, 
-          SEGUNDO_NOMBRE 
-        FROM RE.AC
-        WHERE NACIONALIDAD = :nacionalidad 
-          AND CEDULA = :cedula
-    "#;
+    let usuario = state.registro.find_ac(&nacionalidad, cedula).await?;
 
-    let mut rows = conn.query(sql_persona, &[&nacionalidad, &cedula])
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error query AC: {}", e)))?;
+    auditoria::registrar_consulta(&state, &Contexto::new(&req, &user), auditoria::CONSULTA_AC, Consulta {
+        nacionalidad: Some(nacionalidad),
+        cedula: Some(cedula),
+        resultados: usize::from(usuario.is_some()),
+        ..Default::default()
+    }).await?;
 
-    let row_opt = rows.next().transpose()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error leyendo AC: {}", e)))?;
-
-    let row = match row_opt {
-        Some(r) => r,
+    let usuario = match usuario {
+        Some(u) => u,
         None => {
             // ✅ CORREGIDO: Usar body() en lugar de json() con sintaxis inválida
             return Ok(HttpResponse::NotFound().body("Elector no encontrado"));
         }
     };
 
-    let usuario = UsuarioAC {
-        nacionalidad,
-        cedula,
-        primer_apellido: row.get(0).ok(),
-        segundo_apellido: row.get(1).ok(),
-        primer_nombre: row.get(2).ok(),
-        segundo_nombre: row.get(3).ok(),
-    };
-
     Ok(HttpResponse::Ok().json(usuario))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App, HttpMessage};
+    use std::sync::Arc;
+    use crate::modules::control::pruebas::{self, ControlMemoria};
+
+    #[actix_web::test]
+    async fn get_usuario_by_ac_devuelve_nombres_y_registra() {
+        let ctl = Arc::new(ControlMemoria {
+            permisos: vec![auth::PERM_ADMINISTRAR_USUARIOS],
+            ..Default::default()
+        });
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pruebas::estado(ctl.clone())))
+                .route("/get_usuario_by_ac/{nacionalidad}/{cedula}", web::get().to(get_usuario_by_ac)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/get_usuario_by_ac/e/80000001").to_request();
+        req.extensions_mut().insert(AuthUser { id: 7 });
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: UsuarioAC = test::read_body_json(resp).await;
+        assert_eq!(body.primer_apellido.as_deref(), Some("MARTINEZ"));
+
+        let consultas = ctl.consultas.lock().unwrap();
+        assert_eq!(consultas.len(), 1);
+        assert_eq!(consultas[0].0, auditoria::CONSULTA_AC);
+        assert_eq!(consultas[0].1.nacionalidad.as_deref(), Some("E"));
+    }
+}
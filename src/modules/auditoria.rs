@@ -0,0 +1,454 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{FromRow, Postgres, QueryBuilder};
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use log;
+use crate::modules::auth::{self, AuthUser};
+use crate::modules::paginacion::{self, Pagina};
+use crate::structs::AppState;
+
+// Entidades auditadas
+pub const ENTIDAD_USUARIO: &str = "usuario";
+pub const ENTIDAD_ROL: &str = "rol";
+pub const ENTIDAD_PERMISO: &str = "permiso";
+
+// Quién hace el cambio y desde dónde
+#[derive(Clone, Debug)]
+pub struct Contexto {
+    pub id_actor: i32,
+    pub ip: Option<String>,
+}
+
+// PROXIES_CONFIABLES=ip1,ip2: solo si la conexión viene de uno de ellos se usa
+// X-Forwarded-For; en cualquier otro caso esa cabecera la controla el cliente.
+fn proxies_confiables() -> &'static [IpAddr] {
+    static PROXIES: OnceLock<Vec<IpAddr>> = OnceLock::new();
+    PROXIES.get_or_init(|| {
+        std::env::var("PROXIES_CONFIABLES")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|ip| ip.trim().parse().ok())
+            .collect()
+    })
+}
+
+// Cada proxy agrega al final de X-Forwarded-For: se recorre de derecha a izquierda y se toma
+// la primera IP que no es un proxy confiable (lo que queda más a la izquierda lo escribe el cliente).
+// Ante un valor ilegible se detiene y se queda con el último salto confiable.
+fn ip_real(peer: IpAddr, forwarded_for: &[&str], proxies: &[IpAddr]) -> IpAddr {
+    let mut ip = peer;
+    if !proxies.contains(&ip) {
+        return ip;
+    }
+    for valor in forwarded_for.iter().rev().flat_map(|h| h.rsplit(',')) {
+        match valor.trim().parse::<IpAddr>() {
+            Ok(anterior) if proxies.contains(&anterior) => ip = anterior,
+            Ok(anterior) => return anterior,
+            Err(_) => break,
+        }
+    }
+    ip
+}
+
+fn ip_cliente(req: &HttpRequest) -> Option<String> {
+    let peer = req.peer_addr()?.ip();
+    let forwarded_for: Vec<&str> = req
+        .headers()
+        .get_all("x-forwarded-for")
+        .filter_map(|h| h.to_str().ok())
+        .collect();
+    Some(ip_real(peer, &forwarded_for, proxies_confiables()).to_string())
+}
+
+impl Contexto {
+    pub fn new(req: &HttpRequest, user: &AuthUser) -> Self {
+        Contexto {
+            id_actor: user.id,
+            ip: ip_cliente(req),
+        }
+    }
+}
+
+// Elimina cualquier campo de password (hash o claro) antes de guardar
+fn sin_secretos(valor: Value) -> Value {
+    match valor {
+        Value::Object(mapa) => Value::Object(
+            mapa.into_iter()
+                .filter(|(k, _)| !k.to_lowercase().contains("password"))
+                .map(|(k, v)| (k, sin_secretos(v)))
+                .collect(),
+        ),
+        otro => otro,
+    }
+}
+
+// Solo los campos que cambiaron: (antes, despues)
+pub fn diferencias(antes: &Value, despues: &Value) -> (Value, Value) {
+    let (a, d) = match (antes.as_object(), despues.as_object()) {
+        (Some(a), Some(d)) => (a, d),
+        _ => return (antes.clone(), despues.clone()),
+    };
+
+    let claves: BTreeSet<&String> = a.keys().chain(d.keys()).collect();
+    let mut diff_antes = Map::new();
+    let mut diff_despues = Map::new();
+    for k in claves {
+        let va = a.get(k).cloned().unwrap_or(Value::Null);
+        let vd = d.get(k).cloned().unwrap_or(Value::Null);
+        if va != vd {
+            diff_antes.insert(k.clone(), va);
+            diff_despues.insert(k.clone(), vd);
+        }
+    }
+    (Value::Object(diff_antes), Value::Object(diff_despues))
+}
+
+pub fn a_json<T: Serialize>(valor: &T) -> Option<Value> {
+    serde_json::to_value(valor).ok()
+}
+
+// Inserta un registro en auditoria_usuario (usar dentro de la transacción del cambio)
+pub async fn registrar<'c, E>(
+    executor: E,
+    ctx: &Contexto,
+    entidad: &str,
+    id_entidad: Option<i32>,
+    accion: &str,
+    antes: Option<Value>,
+    despues: Option<Value>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO auditoria_usuario (id_actor, entidad, id_entidad, accion, antes, despues, ip)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(ctx.id_actor)
+    .bind(entidad)
+    .bind(id_entidad)
+    .bind(accion)
+    .bind(antes.map(sin_secretos))
+    .bind(despues.map(sin_secretos))
+    .bind(&ctx.ip)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// =====================
+// GET /api/auditoria
+// =====================
+
+#[derive(FromRow, Serialize)]
+pub struct RegistroAuditoria {
+    pub id: i64,
+    pub id_actor: i32,
+    pub login_actor: Option<String>,
+    pub entidad: String,
+    pub id_entidad: Option<i32>,
+    pub accion: String,
+    pub antes: Option<Value>,
+    pub despues: Option<Value>,
+    pub ip: Option<String>,
+    pub fecha: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct AuditoriaQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub id_actor: Option<i32>,
+    pub entidad: Option<String>,
+    pub id_entidad: Option<i32>,
+    pub accion: Option<String>,
+    pub desde: Option<NaiveDate>, // YYYY-MM-DD
+    pub hasta: Option<NaiveDate>, // YYYY-MM-DD (inclusive)
+}
+
+fn filtros_auditoria(qb: &mut QueryBuilder<'_, Postgres>, f: &AuditoriaQuery) {
+    qb.push(" WHERE 1=1");
+    if let Some(id_actor) = f.id_actor {
+        qb.push(" AND a.id_actor = ").push_bind(id_actor);
+    }
+    if let Some(entidad) = f.entidad.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        qb.push(" AND a.entidad = ").push_bind(entidad.to_lowercase());
+    }
+    if let Some(id_entidad) = f.id_entidad {
+        qb.push(" AND a.id_entidad = ").push_bind(id_entidad);
+    }
+    if let Some(accion) = f.accion.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        qb.push(" AND a.accion = ").push_bind(accion.to_lowercase());
+    }
+    if let Some(desde) = f.desde {
+        qb.push(" AND a.fecha >= ").push_bind(desde);
+    }
+    if let Some(hasta) = f.hasta {
+        qb.push(" AND a.fecha < ").push_bind(hasta).push(" + 1");
+    }
+}
+
+pub async fn get_auditoria(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<AuditoriaQuery>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_VER_AUDITORIA).await {
+        return e.error_response();
+    }
+
+    let (page, page_size, offset) = paginacion::normalizar(query.page, query.page_size, 50, 500);
+
+    let mut qb_total = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM auditoria_usuario a");
+    filtros_auditoria(&mut qb_total, &query);
+
+    let mut qb_items = QueryBuilder::<Postgres>::new(
+        "SELECT a.id, a.id_actor, u.login AS login_actor, a.entidad, a.id_entidad, a.accion,
+                a.antes, a.despues, a.ip, a.fecha
+         FROM auditoria_usuario a
+         LEFT JOIN usuario u ON u.id = a.id_actor",
+    );
+    filtros_auditoria(&mut qb_items, &query);
+    qb_items.push(" ORDER BY a.fecha DESC, a.id DESC LIMIT ").push_bind(page_size);
+    qb_items.push(" OFFSET ").push_bind(offset);
+
+    let resultado = async {
+        let total = qb_total.build_query_scalar::<i64>().fetch_one(&app_state.pool_pg).await?;
+        let items = qb_items.build_query_as::<RegistroAuditoria>().fetch_all(&app_state.pool_pg).await?;
+        Ok::<_, sqlx::Error>((total, items))
+    }
+    .await;
+
+    match resultado {
+        Ok((total, items)) => HttpResponse::Ok().json(Pagina {
+            items,
+            total,
+            page,
+            page_size,
+        }),
+        Err(e) => {
+            log::error!("Error al obtener auditoría: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+// =====================
+// Consultas de datos de ciudadanos
+// =====================
+
+pub const CONSULTA_ELECTOR: &str = "get_elector";
+pub const CONSULTA_ELECTORES: &str = "get_electores";
+pub const CONSULTA_MOVIMIENTOS_RE: &str = "get_movimientos_re";
+pub const CONSULTA_AC: &str = "get_usuario_by_ac";
+
+// Lo que se consultó y cuántos registros se devolvieron
+#[derive(Default)]
+pub struct Consulta {
+    pub nacionalidad: Option<String>,
+    pub cedula: Option<i64>,
+    pub filtros: Option<Value>,
+    pub resultados: usize,
+    pub cedulas_resultado: Option<Vec<i64>>,
+    pub id_motivo: Option<i32>, // ver motivos::validar_motivo
+}
+
+pub async fn insertar_consulta(
+    pool: &sqlx::PgPool,
+    ctx: &Contexto,
+    endpoint: &str,
+    consulta: Consulta,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO auditoria_consulta (id_usuario, endpoint, nacionalidad, cedula, filtros, resultados, cedulas_resultado, id_motivo, ip)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+    )
+    .bind(ctx.id_actor)
+    .bind(endpoint)
+    .bind(consulta.nacionalidad)
+    .bind(consulta.cedula)
+    .bind(consulta.filtros)
+    .bind(consulta.resultados as i32)
+    .bind(consulta.cedulas_resultado)
+    .bind(consulta.id_motivo)
+    .bind(&ctx.ip)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+// Se registra antes de responder: si la bitácora falla, los datos no se entregan
+pub async fn registrar_consulta(
+    state: &AppState,
+    ctx: &Contexto,
+    endpoint: &str,
+    consulta: Consulta,
+) -> Result<(), actix_web::Error> {
+    state.control.registrar_consulta(ctx, endpoint, consulta).await.map_err(|e| {
+        log::error!("Error registrando consulta {} en auditoría: {}", endpoint, e);
+        actix_web::error::ErrorInternalServerError("Error registrando auditoría de la consulta")
+    })
+}
+
+// GET /api/auditoria/consultas?cedula=...&dias=30
+
+#[derive(FromRow, Serialize)]
+pub struct RegistroConsulta {
+    pub id: i64,
+    pub id_usuario: i32,
+    pub login: Option<String>,
+    pub endpoint: String,
+    pub nacionalidad: Option<String>,
+    pub cedula: Option<i64>,
+    pub filtros: Option<Value>,
+    pub resultados: i32,
+    pub motivo: Option<String>,
+    pub ip: Option<String>,
+    pub fecha: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct ConsultasQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub id_usuario: Option<i32>,
+    pub endpoint: Option<String>,
+    pub nacionalidad: Option<String>,
+    pub cedula: Option<i64>, // también encuentra búsquedas por filtros que devolvieron esa cédula
+    pub dias: Option<i32>,   // últimos N días
+    pub desde: Option<NaiveDate>,
+    pub hasta: Option<NaiveDate>,
+}
+
+fn filtros_consultas(qb: &mut QueryBuilder<'_, Postgres>, f: &ConsultasQuery) {
+    qb.push(" WHERE 1=1");
+    if let Some(id_usuario) = f.id_usuario {
+        qb.push(" AND c.id_usuario = ").push_bind(id_usuario);
+    }
+    if let Some(endpoint) = f.endpoint.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        qb.push(" AND c.endpoint = ").push_bind(endpoint.to_lowercase());
+    }
+    let nacionalidad = f.nacionalidad.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    // @> (y no = ANY) para que use el índice GIN de cedulas_resultado
+    match (f.cedula, nacionalidad) {
+        (Some(cedula), Some(nac)) => {
+            qb.push(" AND ((c.cedula = ").push_bind(cedula);
+            qb.push(" AND c.nacionalidad = ").push_bind(nac.to_uppercase());
+            qb.push(") OR c.cedulas_resultado @> ARRAY[").push_bind(cedula).push("]::bigint[])");
+        }
+        (Some(cedula), None) => {
+            qb.push(" AND (c.cedula = ").push_bind(cedula);
+            qb.push(" OR c.cedulas_resultado @> ARRAY[").push_bind(cedula).push("]::bigint[])");
+        }
+        (None, Some(nac)) => {
+            qb.push(" AND c.nacionalidad = ").push_bind(nac.to_uppercase());
+        }
+        (None, None) => {}
+    }
+    if let Some(dias) = f.dias.filter(|d| *d > 0) {
+        qb.push(" AND c.fecha >= NOW() - make_interval(days => ").push_bind(dias).push(")");
+    }
+    if let Some(desde) = f.desde {
+        qb.push(" AND c.fecha >= ").push_bind(desde);
+    }
+    if let Some(hasta) = f.hasta {
+        qb.push(" AND c.fecha < ").push_bind(hasta).push(" + 1");
+    }
+}
+
+pub async fn get_auditoria_consultas(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<ConsultasQuery>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_VER_AUDITORIA).await {
+        return e.error_response();
+    }
+
+    let (page, page_size, offset) = paginacion::normalizar(query.page, query.page_size, 50, 500);
+
+    let mut qb_total = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM auditoria_consulta c");
+    filtros_consultas(&mut qb_total, &query);
+
+    let mut qb_items = QueryBuilder::<Postgres>::new(
+        "SELECT c.id, c.id_usuario, u.login, c.endpoint, c.nacionalidad, c.cedula, c.filtros,
+                c.resultados, m.codigo AS motivo, c.ip, c.fecha
+         FROM auditoria_consulta c
+         LEFT JOIN usuario u ON u.id = c.id_usuario
+         LEFT JOIN motivo_consulta m ON m.id_motivo = c.id_motivo",
+    );
+    filtros_consultas(&mut qb_items, &query);
+    qb_items.push(" ORDER BY c.fecha DESC, c.id DESC LIMIT ").push_bind(page_size);
+    qb_items.push(" OFFSET ").push_bind(offset);
+
+    let resultado = async {
+        let total = qb_total.build_query_scalar::<i64>().fetch_one(&app_state.pool_pg).await?;
+        let items = qb_items.build_query_as::<RegistroConsulta>().fetch_all(&app_state.pool_pg).await?;
+        Ok::<_, sqlx::Error>((total, items))
+    }
+    .await;
+
+    match resultado {
+        Ok((total, items)) => HttpResponse::Ok().json(Pagina {
+            items,
+            total,
+            page,
+            page_size,
+        }),
+        Err(e) => {
+            log::error!("Error al obtener auditoría de consultas: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ip_real, sin_secretos};
+    use serde_json::json;
+    use std::net::IpAddr;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ip_real_no_acepta_cabeceras_de_clientes_directos() {
+        let proxies = [ip("10.0.0.1")];
+        assert_eq!(ip_real(ip("200.1.1.1"), &["6.6.6.6"], &proxies), ip("200.1.1.1"));
+    }
+
+    #[test]
+    fn ip_real_toma_la_primera_ip_no_confiable_desde_la_derecha() {
+        let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+        // El cliente inventa 6.6.6.6; el proxy agrega su IP real 200.1.1.1
+        assert_eq!(ip_real(ip("10.0.0.1"), &["6.6.6.6, 200.1.1.1"], &proxies), ip("200.1.1.1"));
+        assert_eq!(ip_real(ip("10.0.0.1"), &["6.6.6.6", "200.1.1.1, 10.0.0.2"], &proxies), ip("200.1.1.1"));
+        assert_eq!(ip_real(ip("10.0.0.1"), &[], &proxies), ip("10.0.0.1"));
+        assert_eq!(ip_real(ip("10.0.0.1"), &["basura, 10.0.0.2"], &proxies), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn sin_secretos_quita_passwords_y_conserva_marcas() {
+        let limpio = sin_secretos(json!({
+            "password": "hash",
+            "nuevo_password": "claro",
+            "credencial_restablecida": true,
+            "roles": { "password_rol": "x", "nombre": "OPERADOR" }
+        }));
+        assert_eq!(limpio, json!({
+            "credencial_restablecida": true,
+            "roles": { "nombre": "OPERADOR" }
+        }));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::structs::AppState;
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::middleware::Next;
+use actix_web::http::StatusCode;
+use actix_web::{web, Error, HttpMessage, HttpResponse, ResponseError};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use std::fmt;
+
+// Rol que las migraciones buscan por nombre (UPPER(TRIM(rol.nombre))) para sembrar permisos;
+// por eso roles.rs no permite renombrarlo ni eliminarlo. El acceso se decide solo por permisos.
+pub const ROL_ADMINISTRADOR: &str = "ADMINISTRADOR";
+
+// Token restringido emitido a usuarios con expired = 1: solo puede usar RUTA_CAMBIO_PASSWORD
+pub const SCOPE_CAMBIO_PASSWORD: &str = "cambio_password";
+pub const RUTA_CAMBIO_PASSWORD: &str = "/api/me/password";
+
+// Permisos (tabla permiso.nombre)
+pub const PERM_CONSULTAR_ELECTOR: &str = "consultar_elector";
+pub const PERM_VER_MIEMBROS_MESA: &str = "ver_miembros_mesa";
+pub const PERM_EXPORTAR: &str = "exportar";
+pub const PERM_ADMINISTRAR_USUARIOS: &str = "administrar_usuarios";
+pub const PERM_VER_AUDITORIA: &str = "ver_auditoria";
+
+// Permisos que el código consulta por nombre: no se pueden renombrar ni eliminar
+pub const PERMISOS_SISTEMA: &[&str] = &[
+    PERM_CONSULTAR_ELECTOR,
+    PERM_VER_MIEMBROS_MESA,
+    PERM_EXPORTAR,
+    PERM_ADMINISTRAR_USUARIOS,
+    PERM_VER_AUDITORIA,
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    pub iat: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+// Usuario autenticado, disponible en los handlers vía web::ReqData<AuthUser>
+#[derive(Clone, Debug)]
+pub struct AuthUser {
+    pub id: i32,
+}
+
+fn no_autorizado(req: ServiceRequest, mensaje: &str) -> ServiceResponse<BoxBody> {
+    req.into_response(HttpResponse::Unauthorized().json(serde_json::json!({
+        "error": mensaje
+    })))
+}
+
+fn error_interno(req: ServiceRequest) -> ServiceResponse<BoxBody> {
+    req.into_response(HttpResponse::InternalServerError().json(serde_json::json!({
+        "error": "Error interno del servidor"
+    })))
+}
+
+fn bearer_token(req: &ServiceRequest) -> Option<String> {
+    let header = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let token = header.strip_prefix("Bearer ")?.trim();
+    if token.is_empty() {
+        return None;
+    }
+    Some(token.to_string())
+}
+
+// Middleware: valida el JWT emitido por get_login (Authorization: Bearer <token>)
+pub async fn require_auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let (secret, control) = match req.app_data::<web::Data<AppState>>() {
+        Some(state) => (state.jwt_secret.clone(), state.control.clone()),
+        None => {
+            log::error!("AppState no configurado en el middleware de autenticación");
+            return Ok(error_interno(req));
+        }
+    };
+
+    let token = match bearer_token(&req) {
+        Some(t) => t,
+        None => return Ok(no_autorizado(req, "Token requerido")),
+    };
+
+    let claims = match decode::<Claims>(&token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default()) {
+        Ok(data) => data.claims,
+        Err(e) => {
+            let mensaje = match e.kind() {
+                ErrorKind::ExpiredSignature => "Token expirado",
+                _ => "Token inválido",
+            };
+            return Ok(no_autorizado(req, mensaje));
+        }
+    };
+
+    match claims.scope.as_deref() {
+        None => {}
+        Some(SCOPE_CAMBIO_PASSWORD) if req.path() == RUTA_CAMBIO_PASSWORD => {}
+        Some(SCOPE_CAMBIO_PASSWORD) => {
+            return Ok(req.into_response(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Debe cambiar su contraseña",
+                "codigo": "PASSWORD_EXPIRADO"
+            }))));
+        }
+        Some(_) => return Ok(no_autorizado(req, "Token inválido")),
+    }
+
+    let user = match claims.sub.parse::<i32>() {
+        Ok(id) => AuthUser { id },
+        Err(_) => return Ok(no_autorizado(req, "Token inválido")),
+    };
+
+    // Un usuario bloqueado (o eliminado) pierde el acceso aunque su token siga vigente
+    match control.usuario_activo(user.id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(req.into_response(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "Usuario bloqueado",
+                "codigo": "USUARIO_INACTIVO"
+            }))));
+        }
+        Err(e) => {
+            log::error!("Error verificando si el usuario {} está activo: {}", user.id, e);
+            return Ok(error_interno(req));
+        }
+    }
+
+    log::debug!("Usuario {} -> {} {}", user.id, req.method(), req.path());
+    req.extensions_mut().insert(user);
+
+    next.call(req).await.map(ServiceResponse::map_into_boxed_body)
+}
+
+#[derive(Debug)]
+pub enum PermisoError {
+    SinPermiso(String),
+    Interno,
+}
+
+impl fmt::Display for PermisoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermisoError::SinPermiso(p) => write!(f, "No tiene el permiso requerido: {}", p),
+            PermisoError::Interno => write!(f, "Error interno del servidor"),
+        }
+    }
+}
+
+impl ResponseError for PermisoError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PermisoError::SinPermiso(_) => StatusCode::FORBIDDEN,
+            PermisoError::Interno => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(serde_json::json!({
+            "error": self.to_string()
+        }))
+    }
+}
+
+// Un usuario puede tener varios roles: el permiso se evalúa sobre la unión de todos ellos
+pub async fn tiene_permiso(pool: &PgPool, id_usuario: i32, permiso: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (
+            SELECT 1
+            FROM rol_usuario ru
+            JOIN rol_permiso rp ON rp.id_rol = ru.id_rol
+            JOIN permiso p ON p.id_permiso = rp.id_permiso
+            WHERE ru.id_usuario = $1 AND p.nombre = $2
+         )",
+    )
+    .bind(id_usuario)
+    .bind(permiso)
+    .fetch_one(pool)
+    .await
+}
+
+// false si el usuario fue bloqueado (activo = 0) o ya no existe
+pub async fn usuario_activo(pool: &PgPool, id_usuario: i32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM usuario WHERE id = $1 AND activo <> 0)")
+        .bind(id_usuario)
+        .fetch_one(pool)
+        .await
+}
+
+// Guard para handlers: Err(PermisoError) si el usuario no tiene el permiso indicado
+pub async fn require_permiso(state: &AppState, user: &AuthUser, permiso: &str) -> Result<(), PermisoError> {
+    match state.control.tiene_permiso(user.id, permiso).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(PermisoError::SinPermiso(permiso.to_string())),
+        Err(e) => {
+            log::error!("Error verificando permiso {} del usuario {}: {}", permiso, user.id, e);
+            Err(PermisoError::Interno)
+        }
+    }
+}
+
+// Middleware: exige todos los permisos indicados. Cada web::resource de main.rs declara
+// así quién puede usarlo; los handlers repiten su require_permiso por si la ruta cambia.
+// Debe ir dentro de un scope protegido por require_auth.
+pub async fn require_permisos(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+    permisos: &'static [&'static str],
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let user = req.extensions().get::<AuthUser>().cloned();
+    let user = match user {
+        Some(u) => u,
+        None => return Ok(no_autorizado(req, "Token requerido")),
+    };
+
+    let state = match req.app_data::<web::Data<AppState>>() {
+        Some(state) => state.clone(),
+        None => {
+            log::error!("AppState no configurado en el middleware de permisos");
+            return Ok(error_interno(req));
+        }
+    };
+
+    for permiso in permisos {
+        if let Err(e) = require_permiso(&state, &user, permiso).await {
+            return Ok(req.into_response(e.error_response()));
+        }
+    }
+
+    next.call(req).await.map(ServiceResponse::map_into_boxed_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, App};
+    use std::sync::Arc;
+    use crate::modules::control::pruebas::{self, ControlMemoria};
+
+    const EXPORTAR_USUARIOS: &[&str] = &[PERM_ADMINISTRAR_USUARIOS, PERM_EXPORTAR];
+
+    async fn estado_con(permisos: Vec<&'static str>) -> StatusCode {
+        let ctl = Arc::new(ControlMemoria { permisos, ..Default::default() });
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(pruebas::estado(ctl))).service(
+                web::resource("/exportar")
+                    .wrap(from_fn(|req, next| require_permisos(req, next, EXPORTAR_USUARIOS)))
+                    .route(web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/exportar").to_request();
+        req.extensions_mut().insert(AuthUser { id: 7 });
+        test::call_service(&app, req).await.status()
+    }
+
+    #[actix_web::test]
+    async fn require_permisos_exige_todos_los_permisos_de_la_ruta() {
+        assert_eq!(estado_con(vec![PERM_EXPORTAR]).await, StatusCode::FORBIDDEN);
+        assert_eq!(estado_con(vec![PERM_ADMINISTRAR_USUARIOS]).await, StatusCode::FORBIDDEN);
+        assert_eq!(estado_con(vec![PERM_ADMINISTRAR_USUARIOS, PERM_EXPORTAR]).await, StatusCode::OK);
+    }
+
+    async fn con_token(bloqueados: Vec<i32>) -> ServiceResponse {
+        let ctl = Arc::new(ControlMemoria { bloqueados, ..Default::default() });
+        let estado = pruebas::estado(ctl);
+        let ahora = chrono::Utc::now().timestamp() as usize;
+        let claims = Claims { sub: "7".to_string(), exp: ahora + 60, iat: ahora, scope: None };
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(estado.jwt_secret.as_bytes()),
+        )
+        .unwrap();
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(estado)).service(
+                web::resource("/api/me")
+                    .wrap(from_fn(require_auth))
+                    .route(web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/api/me")
+            .insert_header((AUTHORIZATION, format!("Bearer {token}")))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    async fn require_auth_rechaza_tokens_de_usuarios_bloqueados() {
+        assert_eq!(con_token(vec![]).await.status(), StatusCode::OK);
+
+        let resp = con_token(vec![7]).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["codigo"], "USUARIO_INACTIVO");
+    }
+}
@@ -0,0 +1,104 @@
+// Lo que todo handler consulta en Postgres fuera de sus propias tablas: estado y permisos del
+// usuario, motivos de consulta y la bitácora de consultas. Vive en AppState como trait
+// (igual que registro::RegistroElectoralRepo) para poder probar los handlers sin base de datos.
+use futures_util::future::{BoxFuture, FutureExt};
+use sqlx::PgPool;
+use crate::modules::auditoria::{self, Consulta, Contexto};
+use crate::modules::{auth, motivos};
+
+pub trait ControlAcceso: Send + Sync {
+    fn usuario_activo(&self, id_usuario: i32) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+
+    fn tiene_permiso<'a>(&'a self, id_usuario: i32, permiso: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+
+    // id del motivo activo con ese código (ya en mayúsculas)
+    fn motivo_activo<'a>(&'a self, codigo: &'a str) -> BoxFuture<'a, Result<Option<i32>, sqlx::Error>>;
+
+    fn registrar_consulta<'a>(&'a self, ctx: &'a Contexto, endpoint: &'a str, consulta: Consulta)
+        -> BoxFuture<'a, Result<(), sqlx::Error>>;
+}
+
+pub struct ControlPostgres {
+    pool: PgPool,
+}
+
+impl ControlPostgres {
+    pub fn new(pool: PgPool) -> Self {
+        ControlPostgres { pool }
+    }
+}
+
+impl ControlAcceso for ControlPostgres {
+    fn usuario_activo(&self, id_usuario: i32) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        auth::usuario_activo(&self.pool, id_usuario).boxed()
+    }
+
+    fn tiene_permiso<'a>(&'a self, id_usuario: i32, permiso: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        auth::tiene_permiso(&self.pool, id_usuario, permiso).boxed()
+    }
+
+    fn motivo_activo<'a>(&'a self, codigo: &'a str) -> BoxFuture<'a, Result<Option<i32>, sqlx::Error>> {
+        motivos::motivo_activo(&self.pool, codigo).boxed()
+    }
+
+    fn registrar_consulta<'a>(&'a self, ctx: &'a Contexto, endpoint: &'a str, consulta: Consulta)
+        -> BoxFuture<'a, Result<(), sqlx::Error>>
+    {
+        auditoria::insertar_consulta(&self.pool, ctx, endpoint, consulta).boxed()
+    }
+}
+
+// Implementación en memoria para las pruebas de handlers
+#[cfg(test)]
+pub mod pruebas {
+    use super::*;
+    use futures_util::future;
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::{Arc, Mutex};
+    use crate::modules::registro_memoria::RegistroMemoria;
+    use crate::structs::AppState;
+
+    // Los permisos valen para cualquier usuario; todos están activos salvo los de `bloqueados`;
+    // las consultas registradas quedan en `consultas`
+    #[derive(Default)]
+    pub struct ControlMemoria {
+        pub bloqueados: Vec<i32>,
+        pub permisos: Vec<&'static str>,
+        pub motivos: Vec<(&'static str, i32)>,
+        pub consultas: Mutex<Vec<(String, Consulta)>>,
+    }
+
+    impl ControlAcceso for ControlMemoria {
+        fn usuario_activo(&self, id_usuario: i32) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+            future::ready(Ok(!self.bloqueados.contains(&id_usuario))).boxed()
+        }
+
+        fn tiene_permiso<'a>(&'a self, _id_usuario: i32, permiso: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            future::ready(Ok(self.permisos.contains(&permiso))).boxed()
+        }
+
+        fn motivo_activo<'a>(&'a self, codigo: &'a str) -> BoxFuture<'a, Result<Option<i32>, sqlx::Error>> {
+            let id = self.motivos.iter().find(|(c, _)| *c == codigo).map(|(_, id)| *id);
+            future::ready(Ok(id)).boxed()
+        }
+
+        fn registrar_consulta<'a>(&'a self, _ctx: &'a Contexto, endpoint: &'a str, consulta: Consulta)
+            -> BoxFuture<'a, Result<(), sqlx::Error>>
+        {
+            self.consultas.lock().unwrap().push((endpoint.to_string(), consulta));
+            future::ready(Ok(())).boxed()
+        }
+    }
+
+    // AppState con el registro de fixtures/registro_electoral.json; el pool de Postgres
+    // es perezoso y nunca se conecta (los handlers probados solo usan `control`)
+    pub fn estado(control: Arc<ControlMemoria>) -> AppState {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/registro_electoral.json");
+        AppState {
+            pool_pg: PgPoolOptions::new().connect_lazy("postgres://localhost/sin_bd").unwrap(),
+            registro: Arc::new(RegistroMemoria::desde_archivo(fixture).unwrap()),
+            control,
+            jwt_secret: "prueba".to_string(),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+use sqlx::Row;
+use log;
+use crate::modules::auditoria::{self, Contexto, ENTIDAD_USUARIO};
+use crate::modules::auth::AuthUser;
+use crate::modules::passwords::{self, Verificacion};
+use crate::structs::AppState;
+
+#[derive(Deserialize)]
+pub struct CambioPassword {
+    pub password_actual: String,
+    pub password_nuevo: String,
+}
+
+// PUT /api/me/password
+// También accesible con el token restringido que emite get_login cuando expired = 1
+pub async fn cambiar_password(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    body: web::Json<CambioPassword>,
+) -> impl Responder {
+    let pool = &app_state.pool_pg;
+
+    let row = match sqlx::query("SELECT cedula, password FROM usuario WHERE id = $1")
+        .bind(user.id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Usuario no encontrado"
+            }));
+        }
+        Err(e) => {
+            log::error!("Error al obtener usuario {}: {}", user.id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }));
+        }
+    };
+    let cedula: i32 = row.get(0);
+    let password_actual_hash: String = row.get(1);
+
+    if passwords::verificar_password_async(&body.password_actual, &password_actual_hash).await == Verificacion::Invalida {
+        return HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "La contraseña actual es incorrecta"
+        }));
+    }
+
+    if let Err(errores) = passwords::validar_politica(&body.password_nuevo, cedula) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "La contraseña no cumple la política",
+            "details": errores
+        }));
+    }
+
+    // La contraseña actual cuenta como una de las N anteriores
+    let historial_maximo = passwords::historial_maximo();
+    let anteriores = match sqlx::query_scalar::<_, String>(
+        "SELECT password FROM historial_password WHERE id_usuario = $1 ORDER BY fecha DESC, id DESC LIMIT $2"
+    )
+    .bind(user.id)
+    .bind(historial_maximo.saturating_sub(1) as i64)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("Error al obtener historial de passwords: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }));
+        }
+    };
+
+    let hashes: Vec<String> = std::iter::once(password_actual_hash.clone()).chain(anteriores).collect();
+    let reutilizada = passwords::coincide_alguno_async(&body.password_nuevo, hashes).await;
+    if reutilizada {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "La contraseña no cumple la política",
+            "details": [format!("No puede ser igual a las últimas {} contraseñas", historial_maximo)]
+        }));
+    }
+
+    let nuevo_hash = match passwords::hash_password_async(&body.password_nuevo).await {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("Error al generar hash de password: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Error al generar password"
+            }));
+        }
+    };
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<(), sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("INSERT INTO historial_password (id_usuario, password) VALUES ($1, $2)")
+            .bind(user.id)
+            .bind(&password_actual_hash)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE usuario SET password = $1, expired = 0 WHERE id = $2")
+            .bind(&nuevo_hash)
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+
+        auditoria::registrar(&mut *tx, &ctx, ENTIDAD_USUARIO, Some(user.id), "cambio_password", None, None).await?;
+
+        tx.commit().await
+    }
+    .await;
+
+    match resultado {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "mensaje": "Contraseña actualizada"
+        })),
+        Err(e) => {
+            log::error!("Error al cambiar password del usuario {}: {}", user.id, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Error al cambiar contraseña",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
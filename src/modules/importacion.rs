@@ -0,0 +1,162 @@
+// Lectura de archivos CSV / Excel para la carga masiva de usuarios
+use calamine::{open_workbook_auto_from_rs, DataType, Reader};
+use std::io::Cursor;
+
+pub const COLUMNAS: [&str; 5] = ["nacionalidad", "cedula", "nombre", "apellido", "id_rol"];
+
+// Fila del archivo con los valores como texto, sin validar
+#[derive(Debug, Default, Clone)]
+pub struct FilaArchivo {
+    pub fila: usize, // número de fila en el archivo (la 1 es el encabezado)
+    pub nacionalidad: String,
+    pub cedula: String,
+    pub nombre: String,
+    pub apellido: String,
+    pub id_rol: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Formato {
+    Csv,
+    Excel,
+}
+
+pub fn detectar_formato(nombre_archivo: &str) -> Option<Formato> {
+    let nombre = nombre_archivo.trim().to_lowercase();
+    if nombre.ends_with(".csv") {
+        Some(Formato::Csv)
+    } else if nombre.ends_with(".xlsx") || nombre.ends_with(".xls") || nombre.ends_with(".ods") {
+        Some(Formato::Excel)
+    } else {
+        None
+    }
+}
+
+fn leer_csv(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    // Excel en español suele exportar CSV con ';'
+    let primera_linea = bytes.split(|b| *b == b'\n').next().unwrap_or_default();
+    let delimitador = if primera_linea.contains(&b';') { b';' } else { b',' };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimitador)
+        .from_reader(bytes);
+
+    let mut filas = Vec::new();
+    for registro in reader.records() {
+        let registro = registro.map_err(|e| format!("CSV inválido: {}", e))?;
+        // El lector salta las líneas vacías (y no las cuenta en position().line()): se
+        // reponen para que el número de fila sea el del archivo
+        if let Some(pos) = registro.position() {
+            let mut inicio = pos.byte() as usize;
+            while matches!(bytes.get(inicio), Some(b'\r' | b'\n')) {
+                inicio += 1;
+            }
+            let linea = bytes[..inicio].iter().filter(|b| **b == b'\n').count() + 1;
+            while filas.len() + 1 < linea {
+                filas.push(Vec::new());
+            }
+        }
+        filas.push(registro.iter().map(|c| c.trim().to_string()).collect());
+    }
+    Ok(filas)
+}
+
+fn celda_texto(celda: &DataType) -> String {
+    match celda {
+        DataType::Float(f) if f.fract() == 0.0 => format!("{}", *f as i64),
+        otra => otra.to_string().trim().to_string(),
+    }
+}
+
+fn leer_excel(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut libro = open_workbook_auto_from_rs(Cursor::new(bytes.to_vec()))
+        .map_err(|e| format!("Excel inválido: {}", e))?;
+
+    let hoja = libro
+        .worksheet_range_at(0)
+        .ok_or_else(|| "El archivo Excel no tiene hojas".to_string())?
+        .map_err(|e| format!("Error leyendo hoja Excel: {}", e))?;
+
+    Ok(hoja.rows().map(|fila| fila.iter().map(celda_texto).collect()).collect())
+}
+
+// Devuelve las filas de datos (sin encabezado). El encabezado debe contener COLUMNAS, en cualquier orden.
+pub fn leer_archivo(bytes: &[u8], formato: Formato) -> Result<Vec<FilaArchivo>, String> {
+    let filas = match formato {
+        Formato::Csv => leer_csv(bytes)?,
+        Formato::Excel => leer_excel(bytes)?,
+    };
+
+    let mut iter = filas.into_iter();
+    let encabezado: Vec<String> = iter
+        .next()
+        .ok_or_else(|| "El archivo está vacío".to_string())?
+        .iter()
+        .map(|c| c.trim().trim_start_matches('\u{feff}').to_lowercase())
+        .collect();
+
+    let mut indices = [0usize; 5];
+    for (i, columna) in COLUMNAS.iter().enumerate() {
+        indices[i] = encabezado
+            .iter()
+            .position(|c| c == columna)
+            .ok_or_else(|| format!("Falta la columna '{}' (se esperan: {})", columna, COLUMNAS.join(", ")))?;
+    }
+
+    let valor = |fila: &[String], i: usize| fila.get(indices[i]).cloned().unwrap_or_default();
+
+    Ok(iter
+        .enumerate()
+        .filter(|(_, fila)| fila.iter().any(|c| !c.trim().is_empty()))
+        .map(|(n, fila)| FilaArchivo {
+            fila: n + 2,
+            nacionalidad: valor(&fila, 0),
+            cedula: valor(&fila, 1),
+            nombre: valor(&fila, 2),
+            apellido: valor(&fila, 3),
+            id_rol: valor(&fila, 4),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_con_punto_y_coma_bom_y_columnas_en_otro_orden() {
+        let csv = "\u{feff}Cedula;Nombre;Apellido;Nacionalidad;ID_ROL\n\
+                   10000001;María;Pérez;V;2\n\
+                   ;;;;\n\
+                   \n\
+                   80000001; Ana ;Martínez;E;3\n";
+        let filas = leer_archivo(csv.as_bytes(), Formato::Csv).unwrap();
+
+        assert_eq!(filas.len(), 2);
+        assert_eq!(filas[0].fila, 2);
+        assert_eq!(filas[0].nacionalidad, "V");
+        assert_eq!(filas[0].cedula, "10000001");
+        assert_eq!(filas[0].id_rol, "2");
+        // las filas en blanco no se devuelven pero sí cuentan para el número de fila
+        assert_eq!(filas[1].fila, 5);
+        assert_eq!(filas[1].nombre, "Ana");
+    }
+
+    #[test]
+    fn csv_con_coma_y_columna_faltante() {
+        let filas = leer_archivo(b"nacionalidad,cedula,nombre,apellido,id_rol\nV,10000002,Jose,Munoz,1\n", Formato::Csv).unwrap();
+        assert_eq!(filas[0].apellido, "Munoz");
+
+        let error = leer_archivo(b"nacionalidad,cedula,nombre,apellido\nV,1,A,B\n", Formato::Csv).unwrap_err();
+        assert!(error.contains("id_rol"), "{error}");
+    }
+
+    #[test]
+    fn celdas_excel_numericas_sin_decimales() {
+        assert_eq!(celda_texto(&DataType::Float(12345678.0)), "12345678");
+        assert_eq!(celda_texto(&DataType::Float(1.5)), "1.5");
+        assert_eq!(celda_texto(&DataType::String(" V ".to_string())), "V");
+    }
+}
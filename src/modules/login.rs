@@ -1,11 +1,12 @@
 use crate::structs;
+use crate::modules::auth::{Claims, SCOPE_CAMBIO_PASSWORD};
+use crate::modules::passwords::{self, Verificacion};
 use actix_web::{web, HttpResponse};
 use chrono::{Duration, Utc, Local}; // ✅ Importar Local
 use jsonwebtoken::{EncodingKey, Header, encode};
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
 use log::error;
-use sha2::{Digest, Sha256};
 
 #[derive(Deserialize)]
 pub struct InfoLogin {
@@ -25,13 +26,6 @@ struct DatosLogin {
     expired: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: String,
-    exp: usize,
-    iat: usize,
-}
-
 // ✅ Nueva estructura para la hora del servidor
 #[derive(Serialize)]
 struct ServerTimeInfo {
@@ -47,11 +41,15 @@ struct LoginResponse {
     token: String,
     user: DatosLogin,
     server_time: ServerTimeInfo, // ✅ Agregar hora del servidor
+    // true si el token solo sirve para cambiar la contraseña (expired = 1)
+    cambio_password_requerido: bool,
 }
 
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    codigo: Option<String>,
 }
 
 pub async fn get_login(
@@ -62,21 +60,12 @@ pub async fn get_login(
     let password = &info.password;
     let pool = &state.pool_pg;
 
-    // ✅ Calcular SHA256 del password ingresado (formato hexadecimal)
-    let sha256_hash = {
-        let mut hasher = Sha256::new();
-        hasher.update(password);
-        format!("{:x}", hasher.finalize())
-    };
-
-    // ✅ Comparar directamente con VARCHAR (sin decode)
     let row_query = sqlx::query(
-        "SELECT id, nacionalidad, cedula, nombre, apellido, login, activo, expired
+        "SELECT id, nacionalidad, cedula, nombre, apellido, login, activo, expired, password
          FROM usuario
-         WHERE cedula = $1 AND password = $2;",
+         WHERE cedula = $1;",
     )
     .bind(cedula)
-    .bind(&sha256_hash)
     .fetch_optional(pool)
     .await;
 
@@ -86,34 +75,77 @@ pub async fn get_login(
             error!("Error BD: {}", e);
             return HttpResponse::InternalServerError().json(ErrorResponse {
                 error: "Error interno del servidor".to_string(),
+                codigo: None,
             });
         }
     };
 
-    let login_data = match row_query {
-        Some(row) => DatosLogin {
-            id: row.get(0),
-            nacionalidad: row.get(1),
-            cedula: row.get(2),
-            nombre: row.get(3),
-            apellido: row.get(4),
-            login: row.get(5),
-            activo: row.get(6),
-            expired: row.get(7),
-        },
+    let row = match row_query {
+        Some(row) => row,
         None => {
             return HttpResponse::Unauthorized().json(ErrorResponse {
                 error: "Credenciales inválidas".to_string(),
+                codigo: None,
             });
         }
     };
 
+    let password_guardado: String = row.get(8);
+    let verificacion = passwords::verificar_password_async(password, &password_guardado).await;
+    if verificacion == Verificacion::Invalida {
+        return HttpResponse::Unauthorized().json(ErrorResponse {
+            error: "Credenciales inválidas".to_string(),
+            codigo: None,
+        });
+    }
+
+    let login_data = DatosLogin {
+        id: row.get(0),
+        nacionalidad: row.get(1),
+        cedula: row.get(2),
+        nombre: row.get(3),
+        apellido: row.get(4),
+        login: row.get(5),
+        activo: row.get(6),
+        expired: row.get(7),
+    };
+
+    // ✅ Usuario bloqueado (bloquear_usuario): no se emite token
+    if login_data.activo == 0 {
+        return HttpResponse::Forbidden().json(ErrorResponse {
+            error: "Usuario bloqueado".to_string(),
+            codigo: Some("USUARIO_INACTIVO".to_string()),
+        });
+    }
+
+    // ✅ Migración transparente: hash SHA-256 heredado -> Argon2id
+    if verificacion == Verificacion::ValidaLegacy {
+        match passwords::hash_password_async(password).await {
+            Ok(nuevo_hash) => {
+                if let Err(e) = sqlx::query("UPDATE usuario SET password = $1 WHERE id = $2")
+                    .bind(&nuevo_hash)
+                    .bind(login_data.id)
+                    .execute(pool)
+                    .await
+                {
+                    error!("Error migrando password del usuario {}: {}", login_data.id, e);
+                }
+            }
+            Err(e) => error!("Error generando hash Argon2 para usuario {}: {}", login_data.id, e),
+        }
+    }
+
+    // ✅ Password expirado: token restringido al cambio de contraseña
+    let cambio_password_requerido = login_data.expired == 1;
+    let duracion = if cambio_password_requerido { Duration::minutes(15) } else { Duration::hours(4) };
+
     let now = Utc::now();
-    let expiration = match now.checked_add_signed(Duration::hours(4)) {
+    let expiration = match now.checked_add_signed(duracion) {
         Some(exp) => exp.timestamp(),
         None => {
             return HttpResponse::InternalServerError().json(ErrorResponse {
                 error: "Error calculando expiración".to_string(),
+                codigo: None,
             });
         }
     };
@@ -122,6 +154,7 @@ pub async fn get_login(
         sub: login_data.id.to_string(),
         exp: expiration as usize,
         iat: now.timestamp() as usize,
+        scope: cambio_password_requerido.then(|| SCOPE_CAMBIO_PASSWORD.to_string()),
     };
 
     let token = match encode(&Header::default(), &claims, &EncodingKey::from_secret(state.jwt_secret.as_bytes())) {
@@ -130,6 +163,7 @@ pub async fn get_login(
             error!("Error creando token: {}", e);
             return HttpResponse::InternalServerError().json(ErrorResponse {
                 error: "Error generando token".to_string(),
+                codigo: None,
             });
         }
     };
@@ -148,6 +182,7 @@ pub async fn get_login(
         token, 
         user: login_data,
         server_time, // ✅ Incluir en la respuesta
+        cambio_password_requerido,
     };
 
     HttpResponse::Ok().json(response)
@@ -0,0 +1,154 @@
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use sqlx::{FromRow, PgPool};
+use serde::{Deserialize, Serialize};
+use log;
+use crate::modules::auth::{self, AuthUser};
+use crate::structs::AppState;
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct MotivoConsulta {
+    pub id_motivo: i32,
+    pub codigo: String,
+    pub descripcion: String,
+    pub activo: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MotivoInput {
+    pub codigo: String,
+    pub descripcion: String,
+    pub activo: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct MotivosQuery {
+    #[serde(default)]
+    pub todos: bool, // incluir inactivos
+}
+
+fn error_motivo(e: sqlx::Error, contexto: &str) -> HttpResponse {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Ya existe un motivo con ese código"
+        })),
+        _ => {
+            log::error!("{}: {}", contexto, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+fn motivo_invalido(m: &MotivoInput) -> Option<HttpResponse> {
+    if m.codigo.trim().is_empty() || m.descripcion.trim().is_empty() {
+        return Some(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "El código y la descripción del motivo son obligatorios"
+        })));
+    }
+    None
+}
+
+pub async fn motivo_activo(pool: &PgPool, codigo: &str) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>("SELECT id_motivo FROM motivo_consulta WHERE codigo = $1 AND activo = 1")
+        .bind(codigo)
+        .fetch_optional(pool)
+        .await
+}
+
+// Devuelve el id del motivo activo con ese código, o 400 si falta o no existe
+pub async fn validar_motivo(state: &AppState, motivo: Option<&str>) -> Result<i32, actix_web::Error> {
+    let codigo = match motivo.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(c) => c.to_uppercase(),
+        None => return Err(actix_web::error::ErrorBadRequest("Debe indicar el motivo de la consulta")),
+    };
+
+    state
+        .control
+        .motivo_activo(&codigo)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error validando motivo: {}", e)))?
+        .ok_or_else(|| actix_web::error::ErrorBadRequest(format!("Motivo de consulta inválido: {}", codigo)))
+}
+
+// GET /api/motivos-consulta (cualquier usuario autenticado; ?todos=true incluye inactivos)
+pub async fn get_motivos_consulta(
+    app_state: web::Data<AppState>,
+    query: web::Query<MotivosQuery>,
+) -> impl Responder {
+    let sql = if query.todos {
+        "SELECT id_motivo, codigo, descripcion, activo FROM motivo_consulta ORDER BY descripcion"
+    } else {
+        "SELECT id_motivo, codigo, descripcion, activo FROM motivo_consulta WHERE activo = 1 ORDER BY descripcion"
+    };
+
+    match sqlx::query_as::<_, MotivoConsulta>(sql)
+        .fetch_all(&app_state.pool_pg)
+        .await
+    {
+        Ok(motivos) => HttpResponse::Ok().json(motivos),
+        Err(e) => error_motivo(e, "Error al obtener motivos de consulta"),
+    }
+}
+
+pub async fn crear_motivo_consulta(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    motivo: web::Json<MotivoInput>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    if let Some(resp) = motivo_invalido(&motivo) {
+        return resp;
+    }
+
+    match sqlx::query_as::<_, MotivoConsulta>(
+        "INSERT INTO motivo_consulta (codigo, descripcion, activo) VALUES ($1, $2, $3)
+         RETURNING id_motivo, codigo, descripcion, activo"
+    )
+    .bind(motivo.codigo.trim().to_uppercase())
+    .bind(motivo.descripcion.trim())
+    .bind(motivo.activo.unwrap_or(1))
+    .fetch_one(&app_state.pool_pg)
+    .await
+    {
+        Ok(m) => HttpResponse::Created().json(m),
+        Err(e) => error_motivo(e, "Error al crear motivo de consulta"),
+    }
+}
+
+// Los motivos no se eliminan (quedan referenciados en la auditoría): se desactivan con activo = 0
+pub async fn actualizar_motivo_consulta(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+    motivo: web::Json<MotivoInput>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    if let Some(resp) = motivo_invalido(&motivo) {
+        return resp;
+    }
+
+    match sqlx::query_as::<_, MotivoConsulta>(
+        "UPDATE motivo_consulta SET codigo = $1, descripcion = $2, activo = COALESCE($3, activo)
+         WHERE id_motivo = $4
+         RETURNING id_motivo, codigo, descripcion, activo"
+    )
+    .bind(motivo.codigo.trim().to_uppercase())
+    .bind(motivo.descripcion.trim())
+    .bind(motivo.activo)
+    .bind(id.into_inner())
+    .fetch_optional(&app_state.pool_pg)
+    .await
+    {
+        Ok(Some(m)) => HttpResponse::Ok().json(m),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Motivo no encontrado"
+        })),
+        Err(e) => error_motivo(e, "Error al actualizar motivo de consulta"),
+    }
+}
@@ -0,0 +1,186 @@
+// Acceso a Oracle: pool de sesiones compartido (se crea una sola vez en main y vive en AppState)
+// y ejecución de las consultas fuera del executor async, con timeout y cancelación.
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use oracle::pool::{GetMode, Pool, PoolBuilder};
+use oracle::Connection;
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn env_requerida(nombre: &str) -> Result<String, String> {
+    env::var(nombre).map_err(|_| format!("Variable {} faltante", nombre))
+}
+
+fn env_u32(nombre: &str, defecto: u32) -> u32 {
+    env::var(nombre)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(defecto)
+}
+
+// Configuración (opcional) en .env:
+//   ORACLE_POOL_MIN / ORACLE_POOL_MAX / ORACLE_POOL_INCREMENTO: sesiones del pool
+//   ORACLE_STMT_CACHE: sentencias cacheadas por sesión
+//   ORACLE_POOL_ESPERA_MS: espera máxima por una sesión libre cuando el pool está agotado
+pub fn crear_pool() -> Result<Pool, String> {
+    let username = env_requerida("ORACLE_USER")?;
+    let password = env_requerida("ORACLE_PASS")?;
+    let oracle_ip = env_requerida("ORACLE_IP")?;
+    let oracle_port = env_requerida("ORACLE_PORT")?;
+    let oracle_db = env_requerida("ORACLE_DB")?;
+    let connect_string = format!("//{oracle_ip}:{oracle_port}/{oracle_db}");
+
+    let min = env_u32("ORACLE_POOL_MIN", 1);
+    let max = env_u32("ORACLE_POOL_MAX", 10).max(min).max(1);
+    let incremento = env_u32("ORACLE_POOL_INCREMENTO", 1).max(1);
+    let stmt_cache = env_u32("ORACLE_STMT_CACHE", 50);
+    let espera = Duration::from_millis(env_u32("ORACLE_POOL_ESPERA_MS", 5000) as u64);
+
+    PoolBuilder::new(username, password, connect_string)
+        .min_connections(min)
+        .max_connections(max)
+        .connection_increment(incremento)
+        .stmt_cache_size(stmt_cache)
+        .get_mode(GetMode::TimedWait(espera))
+        .build()
+        .map_err(|e| format!("Error creando pool Oracle: {}", e))
+}
+
+#[derive(Debug)]
+pub enum OracleError {
+    // No se pudo obtener una sesión del pool (agotado o Oracle caído)
+    Sesion(oracle::Error),
+    Consulta(oracle::Error),
+    // Se superó el tiempo máximo; la consulta se interrumpe en el servidor
+    Timeout(Duration),
+    Tarea(String),
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OracleError::Sesion(e) => write!(f, "Error obteniendo sesión Oracle: {}", e),
+            OracleError::Consulta(e) => write!(f, "Error consultando Oracle: {}", e),
+            OracleError::Timeout(d) => write!(f, "La consulta Oracle superó {} ms", d.as_millis()),
+            OracleError::Tarea(e) => write!(f, "Error en la tarea Oracle: {}", e),
+        }
+    }
+}
+
+// DPI-1067: call timeout excedido / ORA-01013: operación cancelada (break_execution)
+fn es_timeout(e: &oracle::Error) -> bool {
+    e.dpi_code() == Some(1067) || e.oci_code() == Some(1013)
+}
+
+impl ResponseError for OracleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OracleError::Sesion(_) => StatusCode::SERVICE_UNAVAILABLE,
+            OracleError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            OracleError::Consulta(e) if es_timeout(e) => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error = match self.status_code() {
+            StatusCode::SERVICE_UNAVAILABLE => "Oracle no disponible",
+            StatusCode::GATEWAY_TIMEOUT => "La consulta a Oracle tardó demasiado",
+            _ => "Error consultando Oracle",
+        };
+        HttpResponse::build(self.status_code()).json(serde_json::json!({
+            "error": error,
+            "details": self.to_string()
+        }))
+    }
+}
+
+// Si el futuro se descarta antes de terminar (timeout o el cliente cerró la conexión)
+// se interrumpe la llamada en curso para liberar la sesión.
+struct Cancelacion(Option<Arc<Connection>>);
+
+impl Drop for Cancelacion {
+    fn drop(&mut self) {
+        if let Some(conn) = self.0.take() {
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = conn.break_execution() {
+                    log::warn!("No se pudo cancelar la consulta Oracle: {}", e);
+                }
+            });
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OracleDb {
+    pool: Pool,
+    timeout_consulta: Duration,
+}
+
+impl OracleDb {
+    // ORACLE_TIMEOUT_CONSULTA_MS: límite por consulta (por defecto 15 s)
+    pub fn new(pool: Pool) -> Self {
+        OracleDb {
+            pool,
+            timeout_consulta: Duration::from_millis(env_u32("ORACLE_TIMEOUT_CONSULTA_MS", 15_000).max(1) as u64),
+        }
+    }
+
+    pub fn timeout_consulta(&self) -> Duration {
+        self.timeout_consulta
+    }
+
+    // Ejecuta `f` con una sesión del pool en un hilo de spawn_blocking
+    pub async fn ejecutar<T, F>(&self, f: F) -> Result<T, OracleError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, oracle::Error> + Send + 'static,
+    {
+        self.ejecutar_con_limite(self.timeout_consulta, f).await
+    }
+
+    // Igual que ejecutar, con un límite total distinto (p. ej. lotes de varias consultas).
+    // Cada ida y vuelta a Oracle sigue limitada por timeout_consulta.
+    pub async fn ejecutar_con_limite<T, F>(&self, limite: Duration, f: F) -> Result<T, OracleError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, oracle::Error> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        let timeout_llamada = self.timeout_consulta;
+
+        let conn = tokio::task::spawn_blocking(move || {
+            let conn = pool.get()?;
+            conn.set_call_timeout(Some(timeout_llamada))?;
+            Ok::<_, oracle::Error>(conn)
+        })
+        .await
+        .map_err(|e| OracleError::Tarea(e.to_string()))?
+        .map_err(|e| {
+            log::error!("Error obteniendo sesión Oracle: {}", e);
+            OracleError::Sesion(e)
+        })?;
+
+        let conn = Arc::new(conn);
+        let mut cancelacion = Cancelacion(Some(conn.clone()));
+        let tarea = tokio::task::spawn_blocking(move || f(&conn));
+
+        let resultado = match tokio::time::timeout(limite, tarea).await {
+            Ok(r) => r,
+            Err(_) => {
+                log::warn!("Consulta Oracle cancelada tras {} ms", limite.as_millis());
+                return Err(OracleError::Timeout(limite));
+            }
+        };
+        cancelacion.0 = None;
+
+        resultado
+            .map_err(|e| OracleError::Tarea(e.to_string()))?
+            .map_err(|e| {
+                log::error!("Error consultando Oracle: {}", e);
+                OracleError::Consulta(e)
+            })
+    }
+}
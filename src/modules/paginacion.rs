@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+// Respuesta paginada para las DataTable del front
+#[derive(Serialize)]
+pub struct Pagina<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+// page empieza en 1; page_size se acota a [1, maximo]. Devuelve (page, page_size, offset).
+pub fn normalizar(page: Option<i64>, page_size: Option<i64>, defecto: i64, maximo: i64) -> (i64, i64, i64) {
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size.unwrap_or(defecto).clamp(1, maximo);
+    // saturating: un page enorme no debe desbordar (OFFSET negativo)
+    (page, page_size, (page - 1).saturating_mul(page_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalizar;
+
+    #[test]
+    fn acota_page_y_page_size() {
+        assert_eq!(normalizar(None, None, 20, 500), (1, 20, 0));
+        assert_eq!(normalizar(Some(0), Some(0), 20, 500), (1, 1, 0));
+        assert_eq!(normalizar(Some(3), Some(1000), 20, 500), (3, 500, 1000));
+    }
+
+    #[test]
+    fn page_enorme_no_desborda() {
+        let (_, _, offset) = normalizar(Some(i64::MAX), Some(500), 20, 500);
+        assert_eq!(offset, i64::MAX);
+    }
+}
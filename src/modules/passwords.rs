@@ -0,0 +1,178 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::seq::SliceRandom;
+use sha2::{Digest, Sha256};
+
+// Sin caracteres ambiguos (0/O, 1/l/I) para facilitar la transcripción
+const ALFABETO_INICIAL: &str = "ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnpqrstuvwxyz23456789!@#$%*-_";
+
+// Resultado de comparar un password contra el valor guardado en usuario.password
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verificacion {
+    Valida,
+    // Coincide con el hash SHA-256 heredado: hay que re-hashear con Argon2id
+    ValidaLegacy,
+    Invalida,
+}
+
+// Hash Argon2id en formato PHC ($argon2id$v=19$...)
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut rand::rngs::OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+}
+
+fn sha256_hex(password: &str) -> String {
+    format!("{:x}", Sha256::digest(password.as_bytes()))
+}
+
+pub fn verificar_password(password: &str, almacenado: &str) -> Verificacion {
+    if almacenado.starts_with('$') {
+        return match PasswordHash::new(almacenado) {
+            Ok(hash) if Argon2::default().verify_password(password.as_bytes(), &hash).is_ok() => Verificacion::Valida,
+            Ok(_) => Verificacion::Invalida,
+            Err(e) => {
+                log::error!("Hash de password con formato inválido: {}", e);
+                Verificacion::Invalida
+            }
+        };
+    }
+
+    if sha256_hex(password).eq_ignore_ascii_case(almacenado.trim()) {
+        Verificacion::ValidaLegacy
+    } else {
+        Verificacion::Invalida
+    }
+}
+
+// Argon2id es costoso en CPU: desde handlers async se usan estas versiones, que corren
+// en spawn_blocking para no detener el worker de actix (p. ej. una carga masiva)
+pub async fn hash_password_async(password: &str) -> Result<String, String> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hash_password(&password).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+pub async fn verificar_password_async(password: &str, almacenado: &str) -> Verificacion {
+    let (password, almacenado) = (password.to_string(), almacenado.to_string());
+    tokio::task::spawn_blocking(move || verificar_password(&password, &almacenado))
+        .await
+        .unwrap_or_else(|e| {
+            log::error!("Error en la verificación de password: {}", e);
+            Verificacion::Invalida
+        })
+}
+
+// true si el password coincide con alguno de los hashes (historial); ante un fallo se
+// asume que sí, para rechazar el cambio en lugar de aceptar una contraseña repetida
+pub async fn coincide_alguno_async(password: &str, hashes: Vec<String>) -> bool {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || {
+        hashes.iter().any(|h| verificar_password(&password, h) != Verificacion::Invalida)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        log::error!("Error en la verificación de password: {}", e);
+        true
+    })
+}
+
+fn env_usize(nombre: &str, defecto: usize) -> usize {
+    std::env::var(nombre)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(defecto)
+}
+
+// Cantidad de contraseñas anteriores que no se pueden reutilizar (PASSWORD_HISTORIAL)
+pub fn historial_maximo() -> usize {
+    env_usize("PASSWORD_HISTORIAL", 5)
+}
+
+// Política de contraseñas: devuelve la lista de reglas incumplidas
+pub fn validar_politica(password: &str, cedula: i32) -> Result<(), Vec<String>> {
+    let longitud_minima = env_usize("PASSWORD_LONGITUD_MINIMA", 8);
+    let mut errores = Vec::new();
+
+    if password.chars().count() < longitud_minima {
+        errores.push(format!("Debe tener al menos {} caracteres", longitud_minima));
+    }
+    if !password.chars().any(|c| c.is_lowercase()) {
+        errores.push("Debe contener al menos una letra minúscula".to_string());
+    }
+    if !password.chars().any(|c| c.is_uppercase()) {
+        errores.push("Debe contener al menos una letra mayúscula".to_string());
+    }
+    if !password.chars().any(|c| c.is_ascii_digit()) {
+        errores.push("Debe contener al menos un número".to_string());
+    }
+    if !password.chars().any(|c| !c.is_alphanumeric()) {
+        errores.push("Debe contener al menos un carácter especial".to_string());
+    }
+    if password.contains(&cedula.to_string()) {
+        errores.push("No puede contener la cédula".to_string());
+    }
+
+    if errores.is_empty() {
+        Ok(())
+    } else {
+        Err(errores)
+    }
+}
+
+// Password inicial aleatorio (OsRng). Configurable con PASSWORD_INICIAL_LONGITUD
+// y PASSWORD_INICIAL_ALFABETO. Nunca se guarda ni se registra en claro.
+pub fn generar_password_inicial() -> String {
+    let longitud = env_usize("PASSWORD_INICIAL_LONGITUD", 12).max(8);
+    let alfabeto: Vec<char> = std::env::var("PASSWORD_INICIAL_ALFABETO")
+        .ok()
+        .filter(|a| !a.trim().is_empty())
+        .unwrap_or_else(|| ALFABETO_INICIAL.to_string())
+        .chars()
+        .collect();
+
+    let mut rng = rand::rngs::OsRng;
+    (0..longitud)
+        .filter_map(|_| alfabeto.choose(&mut rng).copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifica_hash_argon2() {
+        let hash = hash_password("Clave#2026").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_eq!(verificar_password("Clave#2026", &hash), Verificacion::Valida);
+        assert_eq!(verificar_password("Clave#2027", &hash), Verificacion::Invalida);
+    }
+
+    #[test]
+    fn sha256_heredado_pide_rehash() {
+        let hex = sha256_hex("Clave#2026");
+        assert_eq!(verificar_password("Clave#2026", &hex), Verificacion::ValidaLegacy);
+        assert_eq!(verificar_password("Clave#2026", &hex.to_uppercase()), Verificacion::ValidaLegacy);
+        assert_eq!(verificar_password("otra", &hex), Verificacion::Invalida);
+    }
+
+    #[test]
+    fn hash_con_formato_invalido_no_coincide() {
+        assert_eq!(verificar_password("Clave#2026", "$abc"), Verificacion::Invalida);
+        assert_eq!(verificar_password("", ""), Verificacion::Invalida);
+    }
+
+    #[test]
+    fn politica_lista_cada_regla_incumplida() {
+        assert_eq!(validar_politica("Clave#2026", 12345678), Ok(()));
+
+        let errores = validar_politica("abc", 12345678).unwrap_err();
+        assert_eq!(errores.len(), 4); // longitud, mayúscula, número, especial
+
+        let errores = validar_politica("Ab#12345678", 12345678).unwrap_err();
+        assert_eq!(errores, vec!["No puede contener la cédula".to_string()]);
+    }
+}
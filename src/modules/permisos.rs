@@ -0,0 +1,341 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+use log;
+use crate::modules::auditoria::{self, Contexto, ENTIDAD_PERMISO, ENTIDAD_ROL};
+use crate::modules::auth::{self, AuthUser};
+use crate::structs::AppState;
+
+#[derive(FromRow, Serialize, Deserialize, Debug)]
+pub struct Permiso {
+    pub id_permiso: i32,
+    pub nombre: String,
+    pub descripcion: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PermisoInput {
+    pub nombre: String,
+    pub descripcion: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PermisosRol {
+    pub id_permisos: Vec<i32>,
+}
+
+fn nombre_valido(nombre: &str) -> bool {
+    !nombre.trim().is_empty()
+}
+
+fn es_permiso_sistema(nombre: &str) -> bool {
+    auth::PERMISOS_SISTEMA.contains(&nombre)
+}
+
+fn error_permiso(e: sqlx::Error, contexto: &str) -> HttpResponse {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Ya existe un permiso con ese nombre"
+        })),
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Rol o permiso inexistente"
+        })),
+        _ => {
+            log::error!("{}: {}", contexto, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+async fn buscar_permiso(pool: &sqlx::PgPool, id_permiso: i32) -> Result<Option<Permiso>, sqlx::Error> {
+    sqlx::query_as::<_, Permiso>("SELECT id_permiso, nombre, descripcion FROM permiso WHERE id_permiso = $1")
+        .bind(id_permiso)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn get_permisos(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    match sqlx::query_as::<_, Permiso>(
+        "SELECT id_permiso, nombre, descripcion FROM permiso ORDER BY id_permiso"
+    )
+    .fetch_all(&app_state.pool_pg)
+    .await
+    {
+        Ok(permisos) => HttpResponse::Ok().json(permisos),
+        Err(e) => {
+            log::error!("Error al obtener permisos: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+pub async fn crear_permiso(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    permiso: web::Json<PermisoInput>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    if !nombre_valido(&permiso.nombre) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "El nombre del permiso es obligatorio"
+        }));
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<Permiso, sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let p = sqlx::query_as::<_, Permiso>(
+            "INSERT INTO permiso (nombre, descripcion) VALUES ($1, $2)
+             RETURNING id_permiso, nombre, descripcion"
+        )
+        .bind(permiso.nombre.trim())
+        .bind(&permiso.descripcion)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        auditoria::registrar(&mut *tx, &ctx, ENTIDAD_PERMISO, Some(p.id_permiso), "crear_permiso", None, auditoria::a_json(&p)).await?;
+
+        tx.commit().await?;
+        Ok(p)
+    }
+    .await;
+
+    match resultado {
+        Ok(p) => HttpResponse::Created().json(p),
+        Err(e) => error_permiso(e, "Error al crear permiso"),
+    }
+}
+
+pub async fn actualizar_permiso(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+    permiso: web::Json<PermisoInput>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    if !nombre_valido(&permiso.nombre) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "El nombre del permiso es obligatorio"
+        }));
+    }
+
+    let id_permiso = id.into_inner();
+
+    let actual = match buscar_permiso(&app_state.pool_pg, id_permiso).await {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Permiso no encontrado"
+            }));
+        }
+        Err(e) => return error_permiso(e, "Error al obtener permiso"),
+    };
+
+    // Solo la descripción de los permisos del sistema es editable
+    if es_permiso_sistema(&actual.nombre) && permiso.nombre.trim() != actual.nombre {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Los permisos del sistema no pueden renombrarse"
+        }));
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<Permiso, sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let p = sqlx::query_as::<_, Permiso>(
+            "UPDATE permiso SET nombre = $1, descripcion = $2 WHERE id_permiso = $3
+             RETURNING id_permiso, nombre, descripcion"
+        )
+        .bind(permiso.nombre.trim())
+        .bind(&permiso.descripcion)
+        .bind(id_permiso)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let (Some(antes), Some(despues)) = (auditoria::a_json(&actual), auditoria::a_json(&p)) {
+            let (antes, despues) = auditoria::diferencias(&antes, &despues);
+            auditoria::registrar(&mut *tx, &ctx, ENTIDAD_PERMISO, Some(id_permiso), "actualizar_permiso", Some(antes), Some(despues)).await?;
+        }
+
+        tx.commit().await?;
+        Ok(p)
+    }
+    .await;
+
+    match resultado {
+        Ok(p) => HttpResponse::Ok().json(p),
+        Err(e) => error_permiso(e, "Error al actualizar permiso"),
+    }
+}
+
+pub async fn eliminar_permiso(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    let id_permiso = id.into_inner();
+
+    let actual = match buscar_permiso(&app_state.pool_pg, id_permiso).await {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Permiso no encontrado"
+            }));
+        }
+        Err(e) => return error_permiso(e, "Error al obtener permiso"),
+    };
+
+    if es_permiso_sistema(&actual.nombre) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Los permisos del sistema no pueden eliminarse"
+        }));
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<(), sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        // Se registra también a qué roles se les retira (ON DELETE CASCADE)
+        let id_roles: Vec<i32> = sqlx::query_scalar(
+            "DELETE FROM rol_permiso WHERE id_permiso = $1 RETURNING id_rol"
+        )
+        .bind(id_permiso)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM permiso WHERE id_permiso = $1")
+            .bind(id_permiso)
+            .execute(&mut *tx)
+            .await?;
+
+        auditoria::registrar(
+            &mut *tx, &ctx, ENTIDAD_PERMISO, Some(id_permiso), "eliminar_permiso",
+            Some(serde_json::json!({ "id_permiso": id_permiso, "nombre": actual.nombre, "id_roles": id_roles })), None,
+        ).await?;
+
+        tx.commit().await
+    }
+    .await;
+
+    match resultado {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => error_permiso(e, "Error al eliminar permiso"),
+    }
+}
+
+pub async fn get_permisos_rol(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    match sqlx::query_as::<_, Permiso>(
+        "SELECT p.id_permiso, p.nombre, p.descripcion
+         FROM rol_permiso rp
+         JOIN permiso p ON p.id_permiso = rp.id_permiso
+         WHERE rp.id_rol = $1
+         ORDER BY p.id_permiso"
+    )
+    .bind(id.into_inner())
+    .fetch_all(&app_state.pool_pg)
+    .await
+    {
+        Ok(permisos) => HttpResponse::Ok().json(permisos),
+        Err(e) => {
+            log::error!("Error al obtener permisos del rol: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+// Reemplaza el conjunto completo de permisos del rol
+pub async fn asignar_permisos_rol(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+    body: web::Json<PermisosRol>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    let id_rol = id.into_inner();
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<(), sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let anteriores: Vec<i32> = sqlx::query_scalar(
+            "DELETE FROM rol_permiso WHERE id_rol = $1 RETURNING id_permiso"
+        )
+        .bind(id_rol)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for id_permiso in &body.id_permisos {
+            sqlx::query("INSERT INTO rol_permiso (id_rol, id_permiso) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(id_rol)
+                .bind(id_permiso)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let mut antes = anteriores;
+        antes.sort_unstable();
+        let mut despues = body.id_permisos.clone();
+        despues.sort_unstable();
+        despues.dedup();
+        if antes != despues {
+            auditoria::registrar(
+                &mut *tx, &ctx, ENTIDAD_ROL, Some(id_rol), "asignar_permisos",
+                Some(serde_json::json!({ "id_permisos": antes })),
+                Some(serde_json::json!({ "id_permisos": despues })),
+            ).await?;
+        }
+
+        tx.commit().await
+    }
+    .await;
+
+    match resultado {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "id_rol": id_rol,
+            "id_permisos": body.id_permisos
+        })),
+        Err(e) => error_permiso(e, &format!("Error al asignar permisos al rol {}", id_rol)),
+    }
+}
@@ -1,77 +1,49 @@
-use actix_web::{web, HttpResponse, Error};
-use oracle::{Connection, Row, RowValue};
-use serde::Deserialize;
-use std::env;
-use std::time::Instant;
+use actix_web::{web, HttpRequest, HttpResponse, Error};
+use serde::{Deserialize, Serialize};
+use crate::modules::auditoria::{self, Consulta, Contexto};
+use crate::modules::auth::{self, AuthUser};
+use crate::modules::motivos;
+use crate::modules::paginacion::{self, Pagina};
+use crate::modules::registro::{
+    ElectorResumen, FiltroTexto, FiltrosElectores, ModoBusqueda, RegistroElectoralRepo, RegistroResult,
+};
+use crate::modules::texto;
+use crate::structs::AppState;
 
 // =====================
 // Movimiento RE (tu código)
 // =====================
-#[derive(serde::Serialize)]
-struct MovimientoRE {
-    CIERRE: i32,
-    NOMBRE_CORTO: Option<String>,
-    ID_LOTE: i32,
-    DESCRIPCION_MOVIMIENTO: String,
-    DESCRIPCION_STATUS: String,
-    FECHA_PROCESO_MOV: String,
-}
-
-impl RowValue for MovimientoRE {
-    fn get(row: &Row) -> std::result::Result<MovimientoRE, oracle::Error> {
-        Ok(MovimientoRE {
-            CIERRE: row.get("CIERRE")?,
-            NOMBRE_CORTO: row.get("NOMBRE_CORTO")?,
-            ID_LOTE: row.get("ID_LOTE")?,
-            DESCRIPCION_MOVIMIENTO: row.get("DESCRIPCION_MOVIMIENTO")?,
-            DESCRIPCION_STATUS: row.get("DESCRIPCION_STATUS")?,
-            FECHA_PROCESO_MOV: row.get("FECHA_PROCESO_MOV")?,
-        })
-    }
-}
-
-fn oracle_conn() -> Result<Connection, oracle::Error> {
-    let username = env::var("ORACLE_USER").unwrap();
-    let password = env::var("ORACLE_PASS").unwrap();
-    let oracle_ip = env::var("ORACLE_IP").unwrap();
-    let oracle_port = env::var("ORACLE_PORT").unwrap();
-    let oracle_db = env::var("ORACLE_DB").unwrap();
-    let connect_string = format!("//{oracle_ip}:{oracle_port}/{oracle_db}");
-    Connection::connect(username, password, connect_string)
+#[derive(Deserialize)]
+pub struct MotivoQuery {
+    pub motivo: Option<String>, // código de motivo_consulta (obligatorio)
 }
 
+// GET /api/get-movimientos-re/{nacionalidad}/{cedula}?motivo=ATENCION_CIUDADANO
 pub async fn get_movimientos_re(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
     path: web::Path<(String, String)>,
+    query: web::Query<MotivoQuery>,
 ) -> Result<HttpResponse, Error> {
+    auth::require_permiso(&state, &user, auth::PERM_CONSULTAR_ELECTOR).await?;
+    let id_motivo = motivos::validar_motivo(&state, query.motivo.as_deref()).await?;
+
     let (nacionalidad, cedula) = path.into_inner();
     let nacionalidad = nacionalidad.to_uppercase();
 
-    let conn = oracle_conn()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error conectando a Oracle: {}", e)))?;
-
-    let sql = "SELECT
-                t.CIERRE, c.NOMBRE_CORTO, t.ID_LOTE, tm.DESCRIPCION DESCRIPCION_MOVIMIENTO,
-                spm.descripcion DESCRIPCION_STATUS, t.FECHA_PROCESO_MOV
-                from re.movimiento t
-                left join re.cierre c
-                on t.cierre=c.codigo
-                left join re.tipo_movimiento tm
-                on t.tipo_movimiento=tm.tipo_movimiento
-                left join re.status_proceso_mov spm
-                on t.status_proceso_mov=spm.codigo
-                where t.nacionalidad= :nacionalidad
-                And T.Cedula_Number= :cedula
-                order by cierre desc";
-
-    let rows = conn.query_as::<MovimientoRE>(sql, &[&nacionalidad, &cedula])
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error ejecutando query: {}", e)))?;
-
-    let mut re_array: Vec<MovimientoRE> = Vec::new();
-    for row_result in rows {
-        let mov = row_result
-            .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error procesando fila: {}", e)))?;
-        re_array.push(mov);
-    }
+    let re_array = state.registro.movimientos(&nacionalidad, &cedula).await?;
+
+    // La cédula llega como texto en la ruta: si no es numérica se guarda tal cual en filtros
+    let cedula_num: Option<i64> = cedula.trim().parse().ok();
+    auditoria::registrar_consulta(&state, &Contexto::new(&req, &user), auditoria::CONSULTA_MOVIMIENTOS_RE, Consulta {
+        nacionalidad: Some(nacionalidad),
+        cedula: cedula_num,
+        filtros: cedula_num.is_none().then(|| serde_json::json!({ "cedula": cedula })),
+        resultados: re_array.len(),
+        id_motivo: Some(id_motivo),
+        ..Default::default()
+    }).await?;
 
     Ok(HttpResponse::Ok().json(&re_array))
 }
@@ -84,6 +56,7 @@ pub async fn get_movimientos_re(
 pub struct ElectorQuery {
     pub nacionalidad: String, // V / E
     pub cedula: i64,          // 28524669
+    pub motivo: Option<String>, // código de motivo_consulta (obligatorio)
 }
 
 #[derive(serde::Serialize, Default)]
@@ -154,7 +127,7 @@ fn clean_geo_desc(s: String) -> String {
         }
     }
 
-    t = t.trim_start_matches(|c: char| c == '-' || c == '—' || c == ':' ).trim().to_string();
+    t = t.trim_start_matches(['-', '—', ':']).trim().to_string();
     t
 }
 
@@ -202,10 +175,21 @@ fn set_no_aplica_miembro(resp: &mut ElectorResponse) {
     resp.miembro_mesa_direccion_centro_capacitacion = Some("No aplica".to_string());
 }
 
-// GET /api/get_elector?nacionalidad=V&cedula=28524669
+// GET /api/get_elector?nacionalidad=V&cedula=28524669&motivo=ATENCION_CIUDADANO
 pub async fn get_elector(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
     query: web::Query<ElectorQuery>,
 ) -> Result<HttpResponse, Error> {
+    auth::require_permiso(&state, &user, auth::PERM_CONSULTAR_ELECTOR).await?;
+    let id_motivo = motivos::validar_motivo(&state, query.motivo.as_deref()).await?;
+    let ver_miembro_mesa = state
+        .control
+        .tiene_permiso(user.id, auth::PERM_VER_MIEMBROS_MESA)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error verificando permisos: {}", e)))?;
+
     let nac = query.nacionalidad.trim().to_uppercase();
     let nacionalidad = nac.chars().next().unwrap_or('V').to_string();
     let cedula = query.cedula;
@@ -217,211 +201,113 @@ pub async fn get_elector(
         return Err(actix_web::error::ErrorBadRequest("cedula inválida"));
     }
 
-    let conn = oracle_conn()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error conectando a Oracle: {}", e)))?;
+    let elector = armar_elector(state.registro.as_ref(), &nacionalidad, cedula, ver_miembro_mesa).await?;
 
-    let mut resp = ElectorResponse {
-        nacionalidad: nacionalidad.clone(),
-        cedula,
+    auditoria::registrar_consulta(&state, &Contexto::new(&req, &user), auditoria::CONSULTA_ELECTOR, Consulta {
+        nacionalidad: Some(nacionalidad),
+        cedula: Some(cedula),
+        resultados: usize::from(elector.is_some()),
+        id_motivo: Some(id_motivo),
         ..Default::default()
-    };
+    }).await?;
+
+    match elector {
+        Some(resp) => Ok(HttpResponse::Ok().json(resp)),
+        None => Ok(HttpResponse::NotFound().body("Elector no encontrado")),
+    }
+}
 
+// Arma la ficha a partir de AC, cuaderno, vista geográfica y miembros de mesa
+async fn armar_elector(
+    repo: &dyn RegistroElectoralRepo,
+    nacionalidad: &str,
+    cedula: i64,
+    ver_miembro_mesa: bool,
+) -> RegistroResult<Option<ElectorResponse>> {
     // ---------------------
     // 1) AC + OBJECION
     // ---------------------
-    let sql_persona = r#"
-        SELECT
-          AC.PRIMER_APELLIDO,
-          AC.SEGUNDO_APELLIDO,
-          AC.PRIMER_NOMBRE,
-          AC.SEGUNDO_NOMBRE,
-          AC.FECHA_NACIMIENTO_4,
-          AC.STATUS_OBJECION,
-          OBJ.DESCRIPCION
-        FROM AC AC
-        JOIN OBJECION OBJ ON AC.STATUS_OBJECION = OBJ.STATUS
-        WHERE AC.NACIONALIDAD = :nacionalidad
-          AND AC.CEDULA = :cedula
-    "#;
-
-    let mut rows = conn.query(sql_persona, &[&nacionalidad, &cedula])
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error query persona: {}", e)))?;
-
-    let row_opt = rows.next().transpose()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error leyendo persona: {}", e)))?;
-
-    let row = match row_opt {
-        Some(r) => r,
-        None => return Ok(HttpResponse::NotFound().body("Elector no encontrado")),
+    let persona = match repo.find_elector(nacionalidad, cedula).await? {
+        Some(p) => p,
+        None => return Ok(None),
     };
 
-    resp.primer_apellido = row.get(0).ok();
-    resp.segundo_apellido = row.get(1).ok();
-    resp.primer_nombre = row.get(2).ok();
-    resp.segundo_nombre = row.get(3).ok();
-
-    let fecha_raw: Option<String> = row.get(4).ok();
-    resp.fecha_nacimiento = fecha_raw.as_deref().and_then(yyyymmdd_to_iso);
-
-    let cod_obj: Option<i64> = row.get(5).ok();
-    resp.codigo_objecion = cod_obj.map(|x| x.to_string());
-    resp.descripcion_objecion = row.get(6).ok();
+    let mut resp = ElectorResponse {
+        nacionalidad: nacionalidad.to_string(),
+        cedula,
+        primer_apellido: persona.primer_apellido,
+        segundo_apellido: persona.segundo_apellido,
+        primer_nombre: persona.primer_nombre,
+        segundo_nombre: persona.segundo_nombre,
+        fecha_nacimiento: persona.fecha_nacimiento.as_deref().and_then(yyyymmdd_to_iso),
+        codigo_objecion: persona.codigo_objecion.map(|x| x.to_string()),
+        descripcion_objecion: persona.descripcion_objecion,
+        ..Default::default()
+    };
 
     // ---------------------
     // 2) instrumentos.cuaderno_actual2
     // ---------------------
-    let sql_cuaderno = r#"
-        SELECT
-          nu_mesa,
-          nu_pagina,
-          nu_renglon,
-          nu_edad_al_evento,
-          fe_evento,
-          cod_estado,
-          cod_municipio,
-          cod_parroquia,
-          nu_centro
-        FROM instrumentos.cuaderno_actual2
-        WHERE co_nacionalidad = :nacionalidad
-          AND nu_cedula = :cedula
-    "#;
-
-    let mut rows2 = conn.query(sql_cuaderno, &[&nacionalidad, &cedula])
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error query cuaderno: {}", e)))?;
-
-    let row2_opt = rows2.next().transpose()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error leyendo cuaderno: {}", e)))?;
-
-    let (cod_estado, cod_municipio, cod_parroquia, cod_centro): (Option<i64>, Option<i64>, Option<i64>, Option<i64>) =
-        if let Some(r2) = row2_opt {
-            resp.numero_mesa = r2.get(0).ok();
-            resp.numero_pagina = r2.get(1).ok();
-            resp.numero_renglon = r2.get(2).ok();
-            resp.edad_ultimo_evento = r2.get(3).ok();
-
-            let fe: Option<String> = r2.get(4).ok();
-            resp.fecha_ultimo_evento = fe.map(|x| x.chars().take(10).collect());
-
-            let ce: Option<i64> = r2.get(5).ok();
-            let cm: Option<i64> = r2.get(6).ok();
-            let cp: Option<i64> = r2.get(7).ok();
-            let cc: Option<i64> = r2.get(8).ok();
-
-            // ✅ aquí el cambio: código centro SIEMPRE con 9 dígitos
-            resp.codigo_centro = cc.map(pad9);
-
-            (ce, cm, cp, cc)
-        } else {
-            (None, None, None, None)
-        };
-
-    // 2.1) Vista geográfica
-    if let (Some(ce), Some(cm), Some(cp), Some(cc)) = (cod_estado, cod_municipio, cod_parroquia, cod_centro) {
-        let sql_geo = r#"
-            SELECT
-              COD_ESTADO,
-              DES_ESTADO,
-              COD_MUNICIPIO,
-              DES_MUNICIPIO,
-              COD_PARROQUIA,
-              DES_PARROQUIA,
-              CODIGO_NUEVO,
-              NOMBRE,
-              DIRECCION
-            FROM RE.V_CENTRO_VOTACION_GEOGRAFICO
-            WHERE CODIGO_NUEVO  = :cc
-              AND COD_ESTADO    = :ce
-              AND COD_MUNICIPIO = :cm
-              AND COD_PARROQUIA = :cp
-        "#;
-
-        let mut rows3 = conn.query(sql_geo, &[&cc, &ce, &cm, &cp])
-            .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error query vista geografica: {}", e)))?;
-
-        if let Some(r3) = rows3.next().transpose()
-            .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error leyendo vista geografica: {}", e)))? {
-
-            let des_estado: Option<String> = r3.get(1).ok();
-            let des_municipio: Option<String> = r3.get(3).ok();
-            let des_parroquia: Option<String> = r3.get(5).ok();
-
-            resp.estado = Some(fmt_geo(ce, des_estado));
-            resp.municipio = Some(fmt_geo(cm, des_municipio));
-            resp.parroquia = Some(fmt_geo(cp, des_parroquia));
-
-            resp.nombre_centro = r3.get(7).ok();
-            resp.direccion_centro = r3.get(8).ok();
+    if let Some(cuaderno) = repo.find_cuaderno(nacionalidad, cedula).await? {
+        resp.numero_mesa = cuaderno.numero_mesa;
+        resp.numero_pagina = cuaderno.numero_pagina;
+        resp.numero_renglon = cuaderno.numero_renglon;
+        resp.edad_ultimo_evento = cuaderno.edad_ultimo_evento;
+        resp.fecha_ultimo_evento = cuaderno.fecha_evento.map(|x| x.chars().take(10).collect());
+
+        // ✅ aquí el cambio: código centro SIEMPRE con 9 dígitos
+        resp.codigo_centro = cuaderno.cod_centro.map(pad9);
+
+        // 2.1) Vista geográfica
+        if let (Some(ce), Some(cm), Some(cp), Some(cc)) =
+            (cuaderno.cod_estado, cuaderno.cod_municipio, cuaderno.cod_parroquia, cuaderno.cod_centro)
+        {
+            if let Some(centro) = repo.find_centro(ce, cm, cp, cc).await? {
+                resp.estado = Some(fmt_geo(ce, centro.des_estado));
+                resp.municipio = Some(fmt_geo(cm, centro.des_municipio));
+                resp.parroquia = Some(fmt_geo(cp, centro.des_parroquia));
+
+                resp.nombre_centro = centro.nombre;
+                resp.direccion_centro = centro.direccion;
+            }
         }
     }
 
     // ---------------------
-    // 3) Miembro de mesa
+    // 3) Miembro de mesa (solo con permiso ver_miembros_mesa)
     // ---------------------
+    if !ver_miembro_mesa {
+        return Ok(Some(resp));
+    }
+
     set_no_aplica_miembro(&mut resp);
 
-    let sql_miembro = r#"
-        SELECT
-          miembro.mesa,
-          cargo_miembro.descripcion_cargo,
-          miembro.centrocap,
-          c_capacitacion.nombre,
-          miembro.tallerdesde,
-          miembro.tallerhasta,
-          miembro.horario,
-          c_capacitacion.direccion
-        FROM miembros_oes miembro,
-             cargos_miembros_oes cargo_miembro,
-             tipos_oes t_oes,
-             MC.centro_capacitacion c_capacitacion
-        WHERE t_oes.tipo_oes = cargo_miembro.tipo_oes
-          AND cargo_miembro.tipo_oes = miembro.timioes
-          AND miembro.cargo = cargo_miembro.cod_cargo
-          AND miembro.centrocap = c_capacitacion.codigo
-          AND miembro.nac = :nacionalidad
-          AND miembro.cedula = :cedula
-    "#;
-
-    let mut rowsm = conn.query(sql_miembro, &[&nacionalidad, &cedula])
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error query miembro_mesa: {}", e)))?;
-
-    if let Some(rm) = rowsm.next().transpose()
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error leyendo miembro_mesa: {}", e)))? {
-
-        let mesa: Option<i64> = rm.get(0).ok();
-        resp.miembro_mesa_numero_mesa = Some(mesa.unwrap_or(0));
-
-        resp.miembro_mesa_cargo = rm.get(1).ok();
-
-        let centrocap: Option<String> = rm.get(2).ok();
-        resp.miembro_mesa_centro_capacitacion = Some(centrocap.unwrap_or_else(|| "0".to_string()));
-
-        resp.miembro_mesa_nombre_centro_capacitacion = rm.get(3).ok();
-
-        let desde: Option<String> = rm.get(4).ok();
-        resp.miembro_mesa_fecha_inicio_capacitacion =
-            desde.as_deref().and_then(ddmmyyyy).or(Some("No aplica".to_string()));
+    if let Some(rm) = repo.find_miembro_mesa(nacionalidad, cedula).await? {
+        resp.miembro_mesa_numero_mesa = Some(rm.mesa.unwrap_or(0));
+        resp.miembro_mesa_cargo = rm.cargo;
+        resp.miembro_mesa_centro_capacitacion = Some(rm.centro_capacitacion.unwrap_or_else(|| "0".to_string()));
+        resp.miembro_mesa_nombre_centro_capacitacion = rm.nombre_centro_capacitacion;
 
-        let hasta: Option<String> = rm.get(5).ok();
+        resp.miembro_mesa_fecha_inicio_capacitacion =
+            rm.taller_desde.as_deref().and_then(ddmmyyyy).or(Some("No aplica".to_string()));
         resp.miembro_mesa_fecha_culminacion_capacitacion =
-            hasta.as_deref().and_then(ddmmyyyy).or(Some("No aplica".to_string()));
-
-        let horario: Option<String> = rm.get(6).ok();
+            rm.taller_hasta.as_deref().and_then(ddmmyyyy).or(Some("No aplica".to_string()));
         resp.miembro_mesa_horario_capacitacion =
-            horario.as_deref().and_then(fmt_horario).or(Some("No aplica".to_string()));
+            rm.horario.as_deref().and_then(fmt_horario).or(Some("No aplica".to_string()));
 
-        resp.miembro_mesa_direccion_centro_capacitacion = rm.get(7).ok();
+        resp.miembro_mesa_direccion_centro_capacitacion = rm.direccion_centro_capacitacion;
     }
 
-    Ok(HttpResponse::Ok().json(resp))
+    Ok(Some(resp))
 }
 
 // =====================
 // NUEVO: Lista de electores (para DataTable)
-// GET /get_electores?primer_nombre=...&fecha_nacimiento=YYYY-MM-DD...
+// GET /get_electores?primer_nombre=...&fecha_nacimiento=YYYY-MM-DD...&page=1&page_size=50
 // FECHA en BD: VARCHAR2(10) formato YYYY-MM-DD
 // =====================
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct ElectoresQuery {
     pub nacionalidad: Option<String>,     // V / E (opcional)
     pub cedula: Option<i64>,              // opcional
@@ -432,19 +318,16 @@ pub struct ElectoresQuery {
     pub primer_apellido: Option<String>,
     pub segundo_apellido: Option<String>,
 
+    // exacto (por defecto) / prefijo / contiene; sin distinguir acentos ni ñ
+    pub modo_primer_nombre: Option<ModoBusqueda>,
+    pub modo_segundo_nombre: Option<ModoBusqueda>,
+    pub modo_primer_apellido: Option<ModoBusqueda>,
+    pub modo_segundo_apellido: Option<ModoBusqueda>,
+
     pub codigo_centro: Option<String>, // opcional
-}
 
-#[derive(serde::Serialize, Default)]
-pub struct ElectorListaItem {
-    pub nacionalidad: String,
-    pub cedula: i64,
-    pub fecha_nacimiento: Option<String>, // YYYY-MM-DD
-    pub primer_nombre: Option<String>,
-    pub segundo_nombre: Option<String>,
-    pub primer_apellido: Option<String>,
-    pub segundo_apellido: Option<String>,
-    pub codigo_centro: Option<String>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
 }
 
 // Normaliza FECHA a "YYYY-MM-DD" (evita 1960--1-0-)
@@ -510,7 +393,14 @@ fn normalize_date(input: Option<&str>) -> Option<String> {
     None
 }
 
-pub async fn get_electores(query: web::Query<ElectoresQuery>) -> Result<HttpResponse, Error> {
+pub async fn get_electores(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<ElectoresQuery>,
+) -> Result<HttpResponse, Error> {
+    auth::require_permiso(&state, &user, auth::PERM_CONSULTAR_ELECTOR).await?;
+
     let q = query.into_inner();
 
     // 1) Validar: al menos 1 dato
@@ -528,138 +418,210 @@ pub async fn get_electores(query: web::Query<ElectoresQuery>) -> Result<HttpResp
         return Err(actix_web::error::ErrorBadRequest("Ingrese al menos un dato"));
     }
 
-    // 2) Conexión Oracle
-    let conn = oracle_conn().map_err(|e| {
-        actix_web::error::ErrorInternalServerError(format!("Error conectando a Oracle: {}", e))
-    })?;
-
-    // 3) FROM + WHERE reutilizable
-    let mut from_where = String::from(r#"
-        FROM V_RE_ACTUAL_CVA
-        WHERE 1=1
-    "#);
-
-    // 4) binds
-    let mut binds_str: Vec<(String, String)> = vec![];
-    let mut binds_i64: Vec<(String, i64)> = vec![];
-
-    fn eq_param(s: &str) -> String {
-        s.trim().to_uppercase()
+    // 2) filtros
+    fn mayusculas(s: &Option<String>) -> Option<String> {
+        s.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()).map(|x| x.to_uppercase())
     }
-
-    // 5) filtros
-    if let Some(nac) = q.nacionalidad.as_ref().map(|x| x.trim().to_uppercase()) {
-        if nac == "V" || nac == "E" {
-            from_where.push_str(" AND NACIONALIDAD = :nacionalidad ");
-            binds_str.push(("nacionalidad".into(), nac));
-        }
+    // Igual que en Oracle: mayúsculas y sin acentos (JOSÉ -> JOSE, MUÑOZ -> MUNOZ)
+    fn nombre(s: &Option<String>, modo: Option<ModoBusqueda>) -> Option<FiltroTexto> {
+        s.as_deref().map(texto::normalizar).filter(|x| !x.is_empty()).map(|valor| FiltroTexto {
+            valor,
+            modo: modo.unwrap_or_default(),
+        })
     }
 
+    let mut filtros = FiltrosElectores {
+        nacionalidad: mayusculas(&q.nacionalidad).filter(|n| n == "V" || n == "E"),
+        primer_nombre: nombre(&q.primer_nombre, q.modo_primer_nombre),
+        segundo_nombre: nombre(&q.segundo_nombre, q.modo_segundo_nombre),
+        primer_apellido: nombre(&q.primer_apellido, q.modo_primer_apellido),
+        segundo_apellido: nombre(&q.segundo_apellido, q.modo_segundo_apellido),
+        codigo_centro: q.codigo_centro.as_ref().map(|x| x.trim().to_string()).filter(|x| !x.is_empty()),
+        ..Default::default()
+    };
+
     if let Some(ced) = q.cedula {
         if ced <= 0 || ced > 99_999_999 {
             return Err(actix_web::error::ErrorBadRequest("cedula inválida"));
         }
-        from_where.push_str(" AND CEDULA = :cedula ");
-        binds_i64.push(("cedula".into(), ced));
+        filtros.cedula = Some(ced);
     }
 
-    // ✅ FECHA en BD: VARCHAR2(10) 'YYYY-MM-DD' -> se compara directo
     if let Some(fnac_input) = q.fecha_nacimiento.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()) {
         let iso = normalize_date(Some(fnac_input))
             .ok_or_else(|| actix_web::error::ErrorBadRequest("fecha_nacimiento inválida (YYYY-MM-DD)"))?;
+        filtros.fecha_nacimiento = Some(iso);
+    }
 
-        from_where.push_str(" AND FECHA = :fecha_nacimiento ");
-        binds_str.push(("fecha_nacimiento".into(), iso));
+    // 3) consulta paginada (page_size máximo 200)
+    let (page, page_size, offset) = paginacion::normalizar(q.page, q.page_size, 50, 200);
+    let (total, mut items): (i64, Vec<ElectorResumen>) =
+        state.registro.search_electores(&filtros, offset, page_size).await?;
+    for item in items.iter_mut() {
+        item.fecha_nacimiento = normalize_date(item.fecha_nacimiento.as_deref());
     }
 
-    if let Some(s) = q.primer_nombre.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()) {
-        from_where.push_str(" AND UPPER(PRIMER_NOMBRE) = :primer_nombre ");
-        binds_str.push(("primer_nombre".into(), eq_param(s)));
+    auditoria::registrar_consulta(&state, &Contexto::new(&req, &user), auditoria::CONSULTA_ELECTORES, Consulta {
+        nacionalidad: filtros.nacionalidad.clone(),
+        cedula: filtros.cedula,
+        filtros: serde_json::to_value(&q).ok(),
+        resultados: items.len(),
+        cedulas_resultado: Some(items.iter().map(|i| i.cedula).collect()),
+        ..Default::default()
+    }).await?;
+
+    Ok(HttpResponse::Ok().json(Pagina {
+        items,
+        total,
+        page,
+        page_size,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App, HttpMessage};
+    use serde_json::Value;
+    use std::sync::Arc;
+    use crate::modules::control::pruebas::{self, ControlMemoria};
+
+    fn control(permisos: Vec<&'static str>) -> Arc<ControlMemoria> {
+        Arc::new(ControlMemoria {
+            permisos,
+            motivos: vec![("ATENCION_CIUDADANO", 1)],
+            ..Default::default()
+        })
     }
 
-    if let Some(s) = q.segundo_nombre.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()) {
-        from_where.push_str(" AND UPPER(SEGUNDO_NOMBRE) = :segundo_nombre ");
-        binds_str.push(("segundo_nombre".into(), eq_param(s)));
+    // Llama a la ruta con un usuario autenticado (lo que deja require_auth en la request)
+    async fn llamar(control: Arc<ControlMemoria>, uri: &str) -> (StatusCode, Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pruebas::estado(control)))
+                .route("/get_elector", web::get().to(get_elector))
+                .route("/get_electores", web::get().to(get_electores))
+                .route("/get-movimientos-re/{nacionalidad}/{cedula}", web::get().to(get_movimientos_re)),
+        )
+        .await;
+        let req = test::TestRequest::get().uri(uri).to_request();
+        req.extensions_mut().insert(AuthUser { id: 7 });
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status();
+        let body = test::read_body(resp).await;
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
     }
 
-    if let Some(s) = q.primer_apellido.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()) {
-        from_where.push_str(" AND UPPER(PRIMER_APELLIDO) = :primer_apellido ");
-        binds_str.push(("primer_apellido".into(), eq_param(s)));
+    fn consultas(control: &ControlMemoria) -> Vec<(String, usize)> {
+        control.consultas.lock().unwrap().iter().map(|(e, c)| (e.clone(), c.resultados)).collect()
     }
 
-    if let Some(s) = q.segundo_apellido.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()) {
-        from_where.push_str(" AND UPPER(SEGUNDO_APELLIDO) = :segundo_apellido ");
-        binds_str.push(("segundo_apellido".into(), eq_param(s)));
+    #[actix_web::test]
+    async fn get_elector_arma_la_ficha_y_registra_la_consulta() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR, auth::PERM_VER_MIEMBROS_MESA]);
+        let (status, body) = llamar(ctl.clone(), "/get_elector?nacionalidad=V&cedula=10000001&motivo=atencion_ciudadano").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["primer_nombre"], "MARIA");
+        assert_eq!(body["fecha_nacimiento"], "1980-01-15");
+        assert_eq!(body["codigo_centro"], "010101001");
+        assert_eq!(body["estado"], "01 - DISTRITO CAPITAL");
+        assert_eq!(body["parroquia"], "01 - CATEDRAL");
+        assert_eq!(body["nombre_centro"], "ESCUELA DE PRUEBA");
+        assert_eq!(body["miembro_mesa_cargo"], "PRESIDENTE");
+        assert_eq!(body["miembro_mesa_horario_capacitacion"], "08:00-12:00");
+
+        assert_eq!(consultas(&ctl), vec![(auditoria::CONSULTA_ELECTOR.to_string(), 1)]);
+        let registro = ctl.consultas.lock().unwrap();
+        assert_eq!(registro[0].1.cedula, Some(10000001));
+        assert_eq!(registro[0].1.id_motivo, Some(1));
     }
 
-    if let Some(s) = q.codigo_centro.as_ref().map(|x| x.trim()).filter(|x| !x.is_empty()) {
-        from_where.push_str(" AND TO_CHAR(CODIGO_CENTRO_VOTACION) = :codigo_centro ");
-        binds_str.push(("codigo_centro".into(), s.to_string()));
+    #[actix_web::test]
+    async fn get_elector_oculta_miembro_de_mesa_sin_permiso() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+        let (status, body) = llamar(ctl, "/get_elector?nacionalidad=V&cedula=10000001&motivo=ATENCION_CIUDADANO").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["miembro_mesa_cargo"], Value::Null);
     }
 
-    // 6) params
-    let mut params: Vec<(&str, &dyn oracle::sql_type::ToSql)> = Vec::new();
-    for (k, v) in &binds_str {
-        params.push((k.as_str(), v as &dyn oracle::sql_type::ToSql));
+    #[actix_web::test]
+    async fn get_elector_no_encontrado_tambien_se_registra() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+        let (status, _) = llamar(ctl.clone(), "/get_elector?nacionalidad=V&cedula=12345&motivo=ATENCION_CIUDADANO").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(consultas(&ctl), vec![(auditoria::CONSULTA_ELECTOR.to_string(), 0)]);
     }
-    for (k, v) in &binds_i64 {
-        params.push((k.as_str(), v as &dyn oracle::sql_type::ToSql));
+
+    #[actix_web::test]
+    async fn get_elector_exige_permiso_y_motivo() {
+        let sin_permiso = control(vec![]);
+        let (status, _) = llamar(sin_permiso.clone(), "/get_elector?nacionalidad=V&cedula=10000001&motivo=ATENCION_CIUDADANO").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(consultas(&sin_permiso).is_empty());
+
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+        let (status, _) = llamar(ctl.clone(), "/get_elector?nacionalidad=V&cedula=10000001").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = llamar(ctl.clone(), "/get_elector?nacionalidad=V&cedula=10000001&motivo=OTRO").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(consultas(&ctl).is_empty());
     }
 
-    // 7) SELECT
-    let sql_select = format!(
-        r#"
-        SELECT 
-            NACIONALIDAD, 
-            CEDULA, 
-            PRIMER_NOMBRE, 
-            SEGUNDO_NOMBRE, 
-            PRIMER_APELLIDO, 
-            SEGUNDO_APELLIDO, 
-            FECHA, 
-            CODIGO_CENTRO_VOTACION
-        {}
-        ORDER BY CEDULA
-        "#,
-        from_where
-    );
-
-    let t1 = Instant::now();
-    let mut rows_data = conn.query_named(&sql_select, &params).map_err(|e| {
-        actix_web::error::ErrorInternalServerError(format!("Error SELECT: {}", e))
-    })?;
-    println!("get_electores SELECT ms = {}", t1.elapsed().as_millis());
-
-    let mut items: Vec<ElectorListaItem> = Vec::new();
-
-    while let Some(row) = rows_data.next().transpose().map_err(|e| {
-        actix_web::error::ErrorInternalServerError(format!("Error leyendo filas: {}", e))
-    })? {
-        let nac: String = row.get(0).unwrap_or_else(|_| "V".to_string());
-        let ced: i64 = row.get(1).unwrap_or(0);
-
-        let primer_nombre: Option<String> = row.get(2).ok();
-        let segundo_nombre: Option<String> = row.get(3).ok();
-        let primer_apellido: Option<String> = row.get(4).ok();
-        let segundo_apellido: Option<String> = row.get(5).ok();
-
-        let fecha_raw: Option<String> = row.get(6).ok();
-        let fecha_iso = normalize_date(fecha_raw.as_deref());
-
-        // si CODIGO_CENTRO_VOTACION ya es VARCHAR2(9), puedes leerlo como String
-        let codigo_centro: Option<String> = row.get(7).ok();
-
-        items.push(ElectorListaItem {
-            nacionalidad: nac,
-            cedula: ced,
-            fecha_nacimiento: fecha_iso,
-            primer_nombre,
-            segundo_nombre,
-            primer_apellido,
-            segundo_apellido,
-            codigo_centro,
-        });
+    #[actix_web::test]
+    async fn get_electores_pagina_y_cuenta_el_total() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+        let (status, body) = llamar(ctl.clone(), "/get_electores?nacionalidad=V&page=2&page_size=1").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["page"], 2);
+        assert_eq!(body["page_size"], 1);
+        assert_eq!(body["items"].as_array().unwrap().len(), 1);
+        assert_eq!(body["items"][0]["cedula"], 10000002);
+
+        // Solo se registran las cédulas entregadas en la página
+        let registro = ctl.consultas.lock().unwrap();
+        assert_eq!(registro[0].0, auditoria::CONSULTA_ELECTORES);
+        assert_eq!(registro[0].1.cedulas_resultado, Some(vec![10000002]));
     }
 
-    Ok(HttpResponse::Ok().json(items))
+    #[actix_web::test]
+    async fn get_electores_ignora_acentos_y_respeta_el_modo() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+
+        let (_, body) = llamar(ctl.clone(), "/get_electores?primer_apellido=munoz").await;
+        assert_eq!(body["total"], 1);
+        assert_eq!(body["items"][0]["primer_apellido"], "MUÑOZ");
+
+        let (_, body) = llamar(ctl.clone(), "/get_electores?primer_nombre=jos%C3%A9").await;
+        assert_eq!(body["total"], 1);
+
+        let (_, body) = llamar(ctl.clone(), "/get_electores?primer_nombre=MAR").await;
+        assert_eq!(body["total"], 0);
+        let (_, body) = llamar(ctl.clone(), "/get_electores?primer_nombre=MAR&modo_primer_nombre=prefijo").await;
+        assert_eq!(body["items"][0]["cedula"], 10000001);
+        let (_, body) = llamar(ctl, "/get_electores?primer_apellido=tine&modo_primer_apellido=contiene").await;
+        assert_eq!(body["items"][0]["cedula"], 80000001);
+    }
+
+    #[actix_web::test]
+    async fn get_electores_sin_filtros_es_400() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+        let (status, _) = llamar(ctl, "/get_electores").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn get_movimientos_re_devuelve_y_registra() {
+        let ctl = control(vec![auth::PERM_CONSULTAR_ELECTOR]);
+        let (status, body) = llamar(ctl.clone(), "/get-movimientos-re/v/10000001?motivo=ATENCION_CIUDADANO").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["DESCRIPCION_MOVIMIENTO"], "CAMBIO DE CENTRO");
+        assert_eq!(consultas(&ctl), vec![(auditoria::CONSULTA_MOVIMIENTOS_RE.to_string(), 1)]);
+    }
 }
@@ -0,0 +1,154 @@
+// Fuente de datos del registro electoral (RE / AC / cuaderno / miembros de mesa).
+// Los handlers solo dependen de este trait: en producción se usa Oracle (registro_oracle)
+// y con REGISTRO_FIXTURE=archivo.json un registro en memoria (registro_memoria).
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use crate::modules::ac::UsuarioAC;
+use crate::modules::oracle_db::OracleError;
+use crate::modules::texto;
+
+// La implementación en memoria nunca devuelve error
+pub type RegistroResult<T> = Result<T, OracleError>;
+
+// RE.AC + OBJECION, valores tal como vienen del registro
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Persona {
+    pub nacionalidad: String,
+    pub cedula: i64,
+    pub primer_nombre: Option<String>,
+    pub segundo_nombre: Option<String>,
+    pub primer_apellido: Option<String>,
+    pub segundo_apellido: Option<String>,
+    pub fecha_nacimiento: Option<String>, // YYYYMMDD
+    pub codigo_objecion: Option<i64>,
+    pub descripcion_objecion: Option<String>,
+}
+
+// instrumentos.cuaderno_actual2
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Cuaderno {
+    pub numero_mesa: Option<i64>,
+    pub numero_pagina: Option<i64>,
+    pub numero_renglon: Option<i64>,
+    pub edad_ultimo_evento: Option<i64>,
+    pub fecha_evento: Option<String>,
+    pub cod_estado: Option<i64>,
+    pub cod_municipio: Option<i64>,
+    pub cod_parroquia: Option<i64>,
+    pub cod_centro: Option<i64>,
+}
+
+// RE.V_CENTRO_VOTACION_GEOGRAFICO
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CentroGeo {
+    pub cod_estado: i64,
+    pub cod_municipio: i64,
+    pub cod_parroquia: i64,
+    pub codigo: i64,
+    pub des_estado: Option<String>,
+    pub des_municipio: Option<String>,
+    pub des_parroquia: Option<String>,
+    pub nombre: Option<String>,
+    pub direccion: Option<String>,
+}
+
+// miembros_oes + centro de capacitación (fechas DDMMYYYY y horario sin formatear)
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MiembroMesa {
+    pub mesa: Option<i64>,
+    pub cargo: Option<String>,
+    pub centro_capacitacion: Option<String>,
+    pub nombre_centro_capacitacion: Option<String>,
+    pub taller_desde: Option<String>,
+    pub taller_hasta: Option<String>,
+    pub horario: Option<String>,
+    pub direccion_centro_capacitacion: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MovimientoRE {
+    pub CIERRE: i32,
+    pub NOMBRE_CORTO: Option<String>,
+    pub ID_LOTE: i32,
+    pub DESCRIPCION_MOVIMIENTO: String,
+    pub DESCRIPCION_STATUS: String,
+    pub FECHA_PROCESO_MOV: String,
+}
+
+// Fila de V_RE_ACTUAL_CVA (listado de get_electores)
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ElectorResumen {
+    pub nacionalidad: String,
+    pub cedula: i64,
+    pub fecha_nacimiento: Option<String>, // YYYY-MM-DD
+    pub primer_nombre: Option<String>,
+    pub segundo_nombre: Option<String>,
+    pub primer_apellido: Option<String>,
+    pub segundo_apellido: Option<String>,
+    pub codigo_centro: Option<String>,
+}
+
+// Cómo se compara un nombre/apellido: exacto, que empiece por o que contenga el texto
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModoBusqueda {
+    #[default]
+    Exacto,
+    Prefijo,
+    Contiene,
+}
+
+// Valor ya normalizado con texto::normalizar (mayúsculas, sin acentos ni ñ)
+#[derive(Clone, Debug)]
+pub struct FiltroTexto {
+    pub valor: String,
+    pub modo: ModoBusqueda,
+}
+
+impl FiltroTexto {
+    pub fn coincide(&self, dato: &str) -> bool {
+        let dato = texto::normalizar(dato);
+        match self.modo {
+            ModoBusqueda::Exacto => dato == self.valor,
+            ModoBusqueda::Prefijo => dato.starts_with(&self.valor),
+            ModoBusqueda::Contiene => dato.contains(&self.valor),
+        }
+    }
+}
+
+// Filtros ya validados de get_electores (fecha en YYYY-MM-DD)
+#[derive(Clone, Debug, Default)]
+pub struct FiltrosElectores {
+    pub nacionalidad: Option<String>,
+    pub cedula: Option<i64>,
+    pub fecha_nacimiento: Option<String>,
+    pub primer_nombre: Option<FiltroTexto>,
+    pub segundo_nombre: Option<FiltroTexto>,
+    pub primer_apellido: Option<FiltroTexto>,
+    pub segundo_apellido: Option<FiltroTexto>,
+    pub codigo_centro: Option<String>,
+}
+
+pub trait RegistroElectoralRepo: Send + Sync {
+    fn find_elector<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<Persona>>>;
+
+    fn find_cuaderno<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<Cuaderno>>>;
+
+    fn find_centro(&self, cod_estado: i64, cod_municipio: i64, cod_parroquia: i64, codigo: i64)
+        -> BoxFuture<'_, RegistroResult<Option<CentroGeo>>>;
+
+    fn find_miembro_mesa<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<MiembroMesa>>>;
+
+    // Una página ordenada por cédula (offset/limit) junto con el total de coincidencias
+    fn search_electores<'a>(&'a self, filtros: &'a FiltrosElectores, offset: i64, limit: i64)
+        -> BoxFuture<'a, RegistroResult<(i64, Vec<ElectorResumen>)>>;
+
+    // La cédula se recibe como texto tal cual llega en la ruta
+    fn movimientos<'a>(&'a self, nacionalidad: &'a str, cedula: &'a str) -> BoxFuture<'a, RegistroResult<Vec<MovimientoRE>>>;
+
+    fn find_ac<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<UsuarioAC>>>;
+
+    // Varias cédulas a la vez (carga masiva); mismo orden que la entrada
+    fn find_ac_lote<'a>(&'a self, cedulas: &'a [(String, i64)]) -> BoxFuture<'a, RegistroResult<Vec<Option<UsuarioAC>>>>;
+}
@@ -0,0 +1,145 @@
+// Registro electoral en memoria cargado desde un JSON, para trabajar sin Oracle: en desarrollo
+// con REGISTRO_FIXTURE y en las pruebas de handlers (control::pruebas::estado).
+// Formato: ver fixtures/registro_electoral.json
+use futures_util::future::{self, BoxFuture, FutureExt};
+use serde::Deserialize;
+use crate::modules::ac::UsuarioAC;
+use crate::modules::registro::{
+    CentroGeo, Cuaderno, ElectorResumen, FiltroTexto, FiltrosElectores, MiembroMesa, MovimientoRE, Persona,
+    RegistroElectoralRepo, RegistroResult,
+};
+
+#[derive(Deserialize, Clone)]
+struct PersonaFixture {
+    #[serde(flatten)]
+    persona: Persona,
+    cuaderno: Option<Cuaderno>,
+    miembro_mesa: Option<MiembroMesa>,
+    #[serde(default)]
+    movimientos: Vec<MovimientoRE>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct RegistroMemoria {
+    #[serde(default)]
+    personas: Vec<PersonaFixture>,
+    #[serde(default)]
+    centros: Vec<CentroGeo>,
+}
+
+fn solo_digitos(s: &str) -> String {
+    s.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+fn coincide(valor: &Option<String>, filtro: &Option<FiltroTexto>) -> bool {
+    match filtro {
+        Some(f) => valor.as_deref().is_some_and(|v| f.coincide(v)),
+        None => true,
+    }
+}
+
+impl RegistroMemoria {
+    pub fn desde_archivo(ruta: &str) -> Result<Self, String> {
+        let contenido = std::fs::read_to_string(ruta)
+            .map_err(|e| format!("No se pudo leer {}: {}", ruta, e))?;
+        serde_json::from_str(&contenido).map_err(|e| format!("Fixture inválido {}: {}", ruta, e))
+    }
+
+    fn persona(&self, nacionalidad: &str, cedula: i64) -> Option<&PersonaFixture> {
+        self.personas
+            .iter()
+            .find(|p| p.persona.nacionalidad == nacionalidad && p.persona.cedula == cedula)
+    }
+
+    fn resumen(p: &PersonaFixture) -> ElectorResumen {
+        ElectorResumen {
+            nacionalidad: p.persona.nacionalidad.clone(),
+            cedula: p.persona.cedula,
+            fecha_nacimiento: p.persona.fecha_nacimiento.clone(),
+            primer_nombre: p.persona.primer_nombre.clone(),
+            segundo_nombre: p.persona.segundo_nombre.clone(),
+            primer_apellido: p.persona.primer_apellido.clone(),
+            segundo_apellido: p.persona.segundo_apellido.clone(),
+            codigo_centro: p.cuaderno.as_ref().and_then(|c| c.cod_centro).map(|c| c.to_string()),
+        }
+    }
+
+    fn ac(&self, nacionalidad: &str, cedula: i64) -> Option<UsuarioAC> {
+        self.persona(nacionalidad, cedula).map(|p| UsuarioAC {
+            nacionalidad: p.persona.nacionalidad.clone(),
+            cedula: p.persona.cedula,
+            primer_apellido: p.persona.primer_apellido.clone(),
+            segundo_apellido: p.persona.segundo_apellido.clone(),
+            primer_nombre: p.persona.primer_nombre.clone(),
+            segundo_nombre: p.persona.segundo_nombre.clone(),
+        })
+    }
+}
+
+impl RegistroElectoralRepo for RegistroMemoria {
+    fn find_elector<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<Persona>>> {
+        future::ready(Ok(self.persona(nacionalidad, cedula).map(|p| p.persona.clone()))).boxed()
+    }
+
+    fn find_cuaderno<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<Cuaderno>>> {
+        future::ready(Ok(self.persona(nacionalidad, cedula).and_then(|p| p.cuaderno.clone()))).boxed()
+    }
+
+    fn find_centro(&self, cod_estado: i64, cod_municipio: i64, cod_parroquia: i64, codigo: i64)
+        -> BoxFuture<'_, RegistroResult<Option<CentroGeo>>>
+    {
+        let centro = self.centros.iter().find(|c| {
+            c.cod_estado == cod_estado && c.cod_municipio == cod_municipio && c.cod_parroquia == cod_parroquia && c.codigo == codigo
+        });
+        future::ready(Ok(centro.cloned())).boxed()
+    }
+
+    fn find_miembro_mesa<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<MiembroMesa>>> {
+        future::ready(Ok(self.persona(nacionalidad, cedula).and_then(|p| p.miembro_mesa.clone()))).boxed()
+    }
+
+    fn search_electores<'a>(&'a self, f: &'a FiltrosElectores, offset: i64, limit: i64)
+        -> BoxFuture<'a, RegistroResult<(i64, Vec<ElectorResumen>)>>
+    {
+        let fecha_filtro = f.fecha_nacimiento.as_deref().map(solo_digitos);
+        let mut items: Vec<ElectorResumen> = self
+            .personas
+            .iter()
+            .map(Self::resumen)
+            .filter(|e| f.nacionalidad.as_ref().is_none_or(|n| &e.nacionalidad == n))
+            .filter(|e| f.cedula.is_none_or(|c| e.cedula == c))
+            .filter(|e| {
+                fecha_filtro.as_ref().is_none_or(|fecha| e.fecha_nacimiento.as_deref().map(solo_digitos).as_ref() == Some(fecha))
+            })
+            .filter(|e| coincide(&e.primer_nombre, &f.primer_nombre))
+            .filter(|e| coincide(&e.segundo_nombre, &f.segundo_nombre))
+            .filter(|e| coincide(&e.primer_apellido, &f.primer_apellido))
+            .filter(|e| coincide(&e.segundo_apellido, &f.segundo_apellido))
+            .filter(|e| f.codigo_centro.as_ref().is_none_or(|c| e.codigo_centro.as_ref() == Some(c)))
+            .collect();
+        items.sort_by_key(|e| e.cedula);
+        let total = items.len() as i64;
+        let pagina = items.into_iter().skip(offset.max(0) as usize).take(limit.max(0) as usize).collect();
+        future::ready(Ok((total, pagina))).boxed()
+    }
+
+    fn movimientos<'a>(&'a self, nacionalidad: &'a str, cedula: &'a str) -> BoxFuture<'a, RegistroResult<Vec<MovimientoRE>>> {
+        let mut movimientos = cedula
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|c| self.persona(nacionalidad, c))
+            .map(|p| p.movimientos.clone())
+            .unwrap_or_default();
+        movimientos.sort_by_key(|m| std::cmp::Reverse(m.CIERRE));
+        future::ready(Ok(movimientos)).boxed()
+    }
+
+    fn find_ac<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<UsuarioAC>>> {
+        future::ready(Ok(self.ac(nacionalidad, cedula))).boxed()
+    }
+
+    fn find_ac_lote<'a>(&'a self, cedulas: &'a [(String, i64)]) -> BoxFuture<'a, RegistroResult<Vec<Option<UsuarioAC>>>> {
+        future::ready(Ok(cedulas.iter().map(|(nac, ced)| self.ac(nac, *ced)).collect())).boxed()
+    }
+}
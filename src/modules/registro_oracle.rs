@@ -0,0 +1,462 @@
+// Implementación Oracle de RegistroElectoralRepo (consultas síncronas vía OracleDb::ejecutar)
+use futures_util::future::{BoxFuture, FutureExt};
+use oracle::{Connection, Row, RowValue};
+use std::time::Instant;
+use crate::modules::ac::UsuarioAC;
+use crate::modules::oracle_db::OracleDb;
+use crate::modules::registro::{
+    CentroGeo, Cuaderno, ElectorResumen, FiltrosElectores, MiembroMesa, ModoBusqueda, MovimientoRE, Persona,
+    RegistroElectoralRepo, RegistroResult,
+};
+
+impl RowValue for MovimientoRE {
+    fn get(row: &Row) -> std::result::Result<MovimientoRE, oracle::Error> {
+        Ok(MovimientoRE {
+            CIERRE: row.get("CIERRE")?,
+            NOMBRE_CORTO: row.get("NOMBRE_CORTO")?,
+            ID_LOTE: row.get("ID_LOTE")?,
+            DESCRIPCION_MOVIMIENTO: row.get("DESCRIPCION_MOVIMIENTO")?,
+            DESCRIPCION_STATUS: row.get("DESCRIPCION_STATUS")?,
+            FECHA_PROCESO_MOV: row.get("FECHA_PROCESO_MOV")?,
+        })
+    }
+}
+
+pub struct RegistroOracle {
+    db: OracleDb,
+}
+
+impl RegistroOracle {
+    pub fn new(db: OracleDb) -> Self {
+        RegistroOracle { db }
+    }
+}
+
+fn buscar_persona(conn: &Connection, nacionalidad: &str, cedula: i64) -> Result<Option<Persona>, oracle::Error> {
+    let sql_persona = r#"
+        SELECT
+          AC.PRIMER_APELLIDO,
+          AC.SEGUNDO_APELLIDO,
+          AC.PRIMER_NOMBRE,
+          AC.SEGUNDO_NOMBRE,
+          AC.FECHA_NACIMIENTO_4,
+          AC.STATUS_OBJECION,
+          OBJ.DESCRIPCION
+        FROM AC AC
+        JOIN OBJECION OBJ ON AC.STATUS_OBJECION = OBJ.STATUS
+        WHERE AC.NACIONALIDAD = :nacionalidad
+          AND AC.CEDULA = :cedula
+    "#;
+
+    let mut rows = conn.query(sql_persona, &[&nacionalidad, &cedula])?;
+    let row = match rows.next().transpose()? {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    Ok(Some(Persona {
+        nacionalidad: nacionalidad.to_string(),
+        cedula,
+        primer_apellido: row.get(0).ok(),
+        segundo_apellido: row.get(1).ok(),
+        primer_nombre: row.get(2).ok(),
+        segundo_nombre: row.get(3).ok(),
+        fecha_nacimiento: row.get(4).ok(),
+        codigo_objecion: row.get(5).ok(),
+        descripcion_objecion: row.get(6).ok(),
+    }))
+}
+
+fn buscar_cuaderno(conn: &Connection, nacionalidad: &str, cedula: i64) -> Result<Option<Cuaderno>, oracle::Error> {
+    let sql_cuaderno = r#"
+        SELECT
+          nu_mesa,
+          nu_pagina,
+          nu_renglon,
+          nu_edad_al_evento,
+          fe_evento,
+          cod_estado,
+          cod_municipio,
+          cod_parroquia,
+          nu_centro
+        FROM instrumentos.cuaderno_actual2
+        WHERE co_nacionalidad = :nacionalidad
+          AND nu_cedula = :cedula
+    "#;
+
+    let mut rows = conn.query(sql_cuaderno, &[&nacionalidad, &cedula])?;
+    Ok(rows.next().transpose()?.map(|r| Cuaderno {
+        numero_mesa: r.get(0).ok(),
+        numero_pagina: r.get(1).ok(),
+        numero_renglon: r.get(2).ok(),
+        edad_ultimo_evento: r.get(3).ok(),
+        fecha_evento: r.get(4).ok(),
+        cod_estado: r.get(5).ok(),
+        cod_municipio: r.get(6).ok(),
+        cod_parroquia: r.get(7).ok(),
+        cod_centro: r.get(8).ok(),
+    }))
+}
+
+fn buscar_centro(conn: &Connection, ce: i64, cm: i64, cp: i64, cc: i64) -> Result<Option<CentroGeo>, oracle::Error> {
+    let sql_geo = r#"
+        SELECT
+          COD_ESTADO,
+          DES_ESTADO,
+          COD_MUNICIPIO,
+          DES_MUNICIPIO,
+          COD_PARROQUIA,
+          DES_PARROQUIA,
+          CODIGO_NUEVO,
+          NOMBRE,
+          DIRECCION
+        FROM RE.V_CENTRO_VOTACION_GEOGRAFICO
+        WHERE CODIGO_NUEVO  = :cc
+          AND COD_ESTADO    = :ce
+          AND COD_MUNICIPIO = :cm
+          AND COD_PARROQUIA = :cp
+    "#;
+
+    let mut rows = conn.query(sql_geo, &[&cc, &ce, &cm, &cp])?;
+    Ok(rows.next().transpose()?.map(|r| CentroGeo {
+        cod_estado: ce,
+        cod_municipio: cm,
+        cod_parroquia: cp,
+        codigo: cc,
+        des_estado: r.get(1).ok(),
+        des_municipio: r.get(3).ok(),
+        des_parroquia: r.get(5).ok(),
+        nombre: r.get(7).ok(),
+        direccion: r.get(8).ok(),
+    }))
+}
+
+fn buscar_miembro_mesa(conn: &Connection, nacionalidad: &str, cedula: i64) -> Result<Option<MiembroMesa>, oracle::Error> {
+    let sql_miembro = r#"
+        SELECT
+          miembro.mesa,
+          cargo_miembro.descripcion_cargo,
+          miembro.centrocap,
+          c_capacitacion.nombre,
+          miembro.tallerdesde,
+          miembro.tallerhasta,
+          miembro.horario,
+          c_capacitacion.direccion
+        FROM miembros_oes miembro,
+             cargos_miembros_oes cargo_miembro,
+             tipos_oes t_oes,
+             MC.centro_capacitacion c_capacitacion
+        WHERE t_oes.tipo_oes = cargo_miembro.tipo_oes
+          AND cargo_miembro.tipo_oes = miembro.timioes
+          AND miembro.cargo = cargo_miembro.cod_cargo
+          AND miembro.centrocap = c_capacitacion.codigo
+          AND miembro.nac = :nacionalidad
+          AND miembro.cedula = :cedula
+    "#;
+
+    let mut rows = conn.query(sql_miembro, &[&nacionalidad, &cedula])?;
+    Ok(rows.next().transpose()?.map(|rm| MiembroMesa {
+        mesa: rm.get(0).ok(),
+        cargo: rm.get(1).ok(),
+        centro_capacitacion: rm.get(2).ok(),
+        nombre_centro_capacitacion: rm.get(3).ok(),
+        taller_desde: rm.get(4).ok(),
+        taller_hasta: rm.get(5).ok(),
+        horario: rm.get(6).ok(),
+        direccion_centro_capacitacion: rm.get(7).ok(),
+    }))
+}
+
+// Escapa los comodines de LIKE en el texto del usuario
+fn escapar_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// FROM + WHERE de V_RE_ACTUAL_CVA con sus binds
+struct FromWhere {
+    sql: String,
+    binds_str: Vec<(String, String)>,
+    binds_i64: Vec<(String, i64)>,
+}
+
+impl FromWhere {
+    fn params(&self) -> Vec<(&str, &dyn oracle::sql_type::ToSql)> {
+        let mut params: Vec<(&str, &dyn oracle::sql_type::ToSql)> = Vec::new();
+        for (k, v) in &self.binds_str {
+            params.push((k.as_str(), v as &dyn oracle::sql_type::ToSql));
+        }
+        for (k, v) in &self.binds_i64 {
+            params.push((k.as_str(), v as &dyn oracle::sql_type::ToSql));
+        }
+        params
+    }
+}
+
+fn from_where_electores(f: &FiltrosElectores) -> FromWhere {
+    let mut from_where = String::from(r#"
+        FROM V_RE_ACTUAL_CVA
+        WHERE 1=1
+    "#);
+    let mut binds_str: Vec<(String, String)> = vec![];
+    let mut binds_i64: Vec<(String, i64)> = vec![];
+
+    if let Some(nac) = &f.nacionalidad {
+        from_where.push_str(" AND NACIONALIDAD = :nacionalidad ");
+        binds_str.push(("nacionalidad".into(), nac.clone()));
+    }
+    if let Some(ced) = f.cedula {
+        from_where.push_str(" AND CEDULA = :cedula ");
+        binds_i64.push(("cedula".into(), ced));
+    }
+    // ✅ FECHA en BD: VARCHAR2(10) 'YYYY-MM-DD' -> se compara directo
+    if let Some(fecha) = &f.fecha_nacimiento {
+        from_where.push_str(" AND FECHA = :fecha_nacimiento ");
+        binds_str.push(("fecha_nacimiento".into(), fecha.clone()));
+    }
+
+    let nombres = [
+        ("PRIMER_NOMBRE_NORM", "primer_nombre", &f.primer_nombre),
+        ("SEGUNDO_NOMBRE_NORM", "segundo_nombre", &f.segundo_nombre),
+        ("PRIMER_APELLIDO_NORM", "primer_apellido", &f.primer_apellido),
+        ("SEGUNDO_APELLIDO_NORM", "segundo_apellido", &f.segundo_apellido),
+    ];
+    // Columnas *_NORM: virtuales e indexadas en RE.AC (oracle/nombres_normalizados.sql),
+    // normalizadas igual que el valor, que llega de texto::normalizar. Exacto y prefijo usan el índice.
+    for (columna, bind, filtro) in nombres {
+        if let Some(filtro) = filtro {
+            let (condicion, valor) = match filtro.modo {
+                ModoBusqueda::Exacto => (format!("{columna} = :{bind}"), filtro.valor.clone()),
+                ModoBusqueda::Prefijo => (
+                    format!("{columna} LIKE :{bind} ESCAPE '\\'"),
+                    format!("{}%", escapar_like(&filtro.valor)),
+                ),
+                ModoBusqueda::Contiene => (
+                    format!("{columna} LIKE :{bind} ESCAPE '\\'"),
+                    format!("%{}%", escapar_like(&filtro.valor)),
+                ),
+            };
+            from_where.push_str(&format!(" AND {condicion} "));
+            binds_str.push((bind.into(), valor));
+        }
+    }
+
+    if let Some(codigo) = &f.codigo_centro {
+        from_where.push_str(" AND TO_CHAR(CODIGO_CENTRO_VOTACION) = :codigo_centro ");
+        binds_str.push(("codigo_centro".into(), codigo.clone()));
+    }
+
+    FromWhere {
+        sql: from_where,
+        binds_str,
+        binds_i64,
+    }
+}
+
+fn buscar_electores(conn: &Connection, f: &FiltrosElectores, offset: i64, limit: i64) -> Result<(i64, Vec<ElectorResumen>), oracle::Error> {
+    let from_where = from_where_electores(f);
+    let params = from_where.params();
+
+    let sql_count = format!("SELECT COUNT(*) {}", from_where.sql);
+    let t0 = Instant::now();
+    let total: i64 = conn.query_row_as_named(&sql_count, &params)?;
+    log::debug!("get_electores COUNT ms = {}", t0.elapsed().as_millis());
+
+    let mut params_pagina = params;
+    params_pagina.push(("offset_filas", &offset));
+    params_pagina.push(("limite_filas", &limit));
+
+    let sql_select = format!(
+        r#"
+        SELECT
+            NACIONALIDAD,
+            CEDULA,
+            PRIMER_NOMBRE,
+            SEGUNDO_NOMBRE,
+            PRIMER_APELLIDO,
+            SEGUNDO_APELLIDO,
+            FECHA,
+            CODIGO_CENTRO_VOTACION
+        {}
+        ORDER BY CEDULA
+        OFFSET :offset_filas ROWS FETCH NEXT :limite_filas ROWS ONLY
+        "#,
+        from_where.sql
+    );
+
+    let t1 = Instant::now();
+    let rows_data = conn.query_named(&sql_select, &params_pagina)?;
+    log::debug!("get_electores SELECT ms = {}", t1.elapsed().as_millis());
+
+    let mut items: Vec<ElectorResumen> = Vec::new();
+    for row in rows_data {
+        let row = row?;
+        items.push(ElectorResumen {
+            nacionalidad: row.get(0).unwrap_or_else(|_| "V".to_string()),
+            cedula: row.get(1).unwrap_or(0),
+            primer_nombre: row.get(2).ok(),
+            segundo_nombre: row.get(3).ok(),
+            primer_apellido: row.get(4).ok(),
+            segundo_apellido: row.get(5).ok(),
+            fecha_nacimiento: row.get(6).ok(),
+            // si CODIGO_CENTRO_VOTACION ya es VARCHAR2(9), puedes leerlo como String
+            codigo_centro: row.get(7).ok(),
+        });
+    }
+    Ok((total, items))
+}
+
+fn buscar_movimientos(conn: &Connection, nacionalidad: &str, cedula: &str) -> Result<Vec<MovimientoRE>, oracle::Error> {
+    let sql = "SELECT
+                t.CIERRE, c.NOMBRE_CORTO, t.ID_LOTE, tm.DESCRIPCION DESCRIPCION_MOVIMIENTO,
+                spm.descripcion DESCRIPCION_STATUS, t.FECHA_PROCESO_MOV
+                from re.movimiento t
+                left join re.cierre c
+                on t.cierre=c.codigo
+                left join re.tipo_movimiento tm
+                on t.tipo_movimiento=tm.tipo_movimiento
+                left join re.status_proceso_mov spm
+                on t.status_proceso_mov=spm.codigo
+                where t.nacionalidad= :nacionalidad
+                And T.Cedula_Number= :cedula
+                order by cierre desc";
+
+    conn.query_as::<MovimientoRE>(sql, &[&nacionalidad, &cedula])?.collect()
+}
+
+// Nombres y apellidos en RE.AC (get_usuario_by_ac y creación de usuarios con validar_ac)
+fn buscar_ac(conn: &Connection, nacionalidad: &str, cedula: i64) -> Result<Option<UsuarioAC>, oracle::Error> {
+    let sql_persona = r#"
+        SELECT
+          PRIMER_APELLIDO,
+          SEGUNDO_APELLIDO,
+          PRIMER_NOMBRE,
+          SEGUNDO_NOMBRE
+        FROM RE.AC
+        WHERE NACIONALIDAD = :nacionalidad
+          AND CEDULA = :cedula
+    "#;
+
+    let mut rows = conn.query(sql_persona, &[&nacionalidad, &cedula])?;
+
+    let row = match rows.next().transpose()? {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    Ok(Some(UsuarioAC {
+        nacionalidad: nacionalidad.to_string(),
+        cedula,
+        primer_apellido: row.get(0).ok(),
+        segundo_apellido: row.get(1).ok(),
+        primer_nombre: row.get(2).ok(),
+        segundo_nombre: row.get(3).ok(),
+    }))
+}
+
+impl RegistroElectoralRepo for RegistroOracle {
+    fn find_elector<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<Persona>>> {
+        let nacionalidad = nacionalidad.to_string();
+        self.db.ejecutar(move |conn| buscar_persona(conn, &nacionalidad, cedula)).boxed()
+    }
+
+    fn find_cuaderno<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<Cuaderno>>> {
+        let nacionalidad = nacionalidad.to_string();
+        self.db.ejecutar(move |conn| buscar_cuaderno(conn, &nacionalidad, cedula)).boxed()
+    }
+
+    fn find_centro(&self, cod_estado: i64, cod_municipio: i64, cod_parroquia: i64, codigo: i64)
+        -> BoxFuture<'_, RegistroResult<Option<CentroGeo>>>
+    {
+        self.db
+            .ejecutar(move |conn| buscar_centro(conn, cod_estado, cod_municipio, cod_parroquia, codigo))
+            .boxed()
+    }
+
+    fn find_miembro_mesa<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<MiembroMesa>>> {
+        let nacionalidad = nacionalidad.to_string();
+        self.db.ejecutar(move |conn| buscar_miembro_mesa(conn, &nacionalidad, cedula)).boxed()
+    }
+
+    fn search_electores<'a>(&'a self, filtros: &'a FiltrosElectores, offset: i64, limit: i64)
+        -> BoxFuture<'a, RegistroResult<(i64, Vec<ElectorResumen>)>>
+    {
+        let filtros = filtros.clone();
+        self.db.ejecutar(move |conn| buscar_electores(conn, &filtros, offset, limit)).boxed()
+    }
+
+    fn movimientos<'a>(&'a self, nacionalidad: &'a str, cedula: &'a str) -> BoxFuture<'a, RegistroResult<Vec<MovimientoRE>>> {
+        let (nacionalidad, cedula) = (nacionalidad.to_string(), cedula.to_string());
+        self.db.ejecutar(move |conn| buscar_movimientos(conn, &nacionalidad, &cedula)).boxed()
+    }
+
+    fn find_ac<'a>(&'a self, nacionalidad: &'a str, cedula: i64) -> BoxFuture<'a, RegistroResult<Option<UsuarioAC>>> {
+        let nacionalidad = nacionalidad.to_string();
+        self.db.ejecutar(move |conn| buscar_ac(conn, &nacionalidad, cedula)).boxed()
+    }
+
+    // Una sola sesión para todo el lote; el límite total crece con la cantidad de cédulas
+    fn find_ac_lote<'a>(&'a self, cedulas: &'a [(String, i64)]) -> BoxFuture<'a, RegistroResult<Vec<Option<UsuarioAC>>>> {
+        let cedulas = cedulas.to_vec();
+        let limite = self.db.timeout_consulta() * (cedulas.len().max(1) as u32);
+        self.db
+            .ejecutar_con_limite(limite, move |conn| {
+                cedulas.iter().map(|(nac, ced)| buscar_ac(conn, nac, *ced)).collect()
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::texto;
+
+    const DDL: &str = include_str!("../../oracle/nombres_normalizados.sql");
+    const ACENTOS: &str = "ÁÀÄÂÃÉÈËÊÍÌÏÎÓÒÖÔÕÚÙÜÛÑÇ";
+    const SIN_ACENTOS: &str = "AAAAAEEEEIIIIOOOOOUUUUNC";
+
+    // Reproduce en Rust la expresión de las columnas *_NORM
+    fn como_oracle(s: &str) -> String {
+        let colapsado = s.split(char::is_whitespace).filter(|p| !p.is_empty()).collect::<Vec<_>>().join(" ");
+        colapsado
+            .to_uppercase()
+            .chars()
+            .map(|c| match ACENTOS.chars().position(|a| a == c) {
+                Some(i) => SIN_ACENTOS.chars().nth(i).unwrap(),
+                None => c,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn translate_coincide_con_quitar_acentos() {
+        assert_eq!(ACENTOS.chars().count(), SIN_ACENTOS.chars().count());
+        for (a, b) in ACENTOS.chars().zip(SIN_ACENTOS.chars()) {
+            assert_eq!(texto::quitar_acentos(&a.to_string()), b.to_string());
+        }
+    }
+
+    #[test]
+    fn columna_y_valor_se_normalizan_igual() {
+        let nombres = [
+            "María  de los Ángeles",
+            " JOSÉ\tLUIS ",
+            "muñoz",
+            "Güiria Çelis",
+            "PEREZ",
+        ];
+        for n in nombres {
+            assert_eq!(como_oracle(n), texto::normalizar(n), "{n:?}");
+        }
+    }
+
+    #[test]
+    fn ddl_usa_la_normalizacion_de_rust() {
+        for columna in ["PRIMER_NOMBRE", "SEGUNDO_NOMBRE", "PRIMER_APELLIDO", "SEGUNDO_APELLIDO"] {
+            let expresion = format!(
+                "{columna}_NORM AS (TRANSLATE(UPPER(TRIM(REGEXP_REPLACE({columna}, '[[:space:]]+', ' '))), '{ACENTOS}', '{SIN_ACENTOS}')) VIRTUAL"
+            );
+            assert!(DDL.contains(&expresion), "{columna}");
+            assert!(DDL.contains(&format!("ON RE.AC ({columna}_NORM)")), "{columna}");
+        }
+    }
+}
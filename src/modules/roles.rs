@@ -0,0 +1,246 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+use log;
+use crate::modules::auditoria::{self, Contexto, ENTIDAD_ROL};
+use crate::modules::auth::{self, AuthUser};
+use crate::structs::AppState;
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct Rol {
+    pub id_rol: i32,
+    pub nombre: String,
+    pub descripcion: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RolInput {
+    pub nombre: String,
+    pub descripcion: Option<String>,
+}
+
+fn es_rol_administrador(nombre: &str) -> bool {
+    nombre.trim().eq_ignore_ascii_case(auth::ROL_ADMINISTRADOR)
+}
+
+fn error_rol(e: sqlx::Error, contexto: &str) -> HttpResponse {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Ya existe un rol con ese nombre"
+        })),
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "El rol tiene usuarios asignados"
+        })),
+        _ => {
+            log::error!("{}: {}", contexto, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+pub async fn get_roles(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    match sqlx::query_as::<_, Rol>(
+        "SELECT id_rol, nombre, descripcion FROM rol ORDER BY id_rol"
+    )
+    .fetch_all(&app_state.pool_pg)
+    .await
+    {
+        Ok(roles) => HttpResponse::Ok().json(roles),
+        Err(e) => {
+            log::error!("Error al obtener roles: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }))
+        }
+    }
+}
+
+pub async fn crear_rol(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    rol: web::Json<RolInput>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    if rol.nombre.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "El nombre del rol es obligatorio"
+        }));
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<Rol, sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let r = sqlx::query_as::<_, Rol>(
+            "INSERT INTO rol (nombre, descripcion) VALUES ($1, $2)
+             RETURNING id_rol, nombre, descripcion"
+        )
+        .bind(rol.nombre.trim())
+        .bind(&rol.descripcion)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        auditoria::registrar(&mut *tx, &ctx, ENTIDAD_ROL, Some(r.id_rol), "crear_rol", None, auditoria::a_json(&r)).await?;
+
+        tx.commit().await?;
+        Ok(r)
+    }
+    .await;
+
+    match resultado {
+        Ok(r) => HttpResponse::Created().json(r),
+        Err(e) => error_rol(e, "Error al crear rol"),
+    }
+}
+
+// Renombrar y/o cambiar la descripción
+pub async fn actualizar_rol(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+    rol: web::Json<RolInput>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    if rol.nombre.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "El nombre del rol es obligatorio"
+        }));
+    }
+    let id_rol = id.into_inner();
+
+    let actual = match sqlx::query_as::<_, Rol>("SELECT id_rol, nombre, descripcion FROM rol WHERE id_rol = $1")
+        .bind(id_rol)
+        .fetch_optional(&app_state.pool_pg)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Rol no encontrado"
+            }));
+        }
+        Err(e) => return error_rol(e, "Error al obtener rol"),
+    };
+
+    // Las migraciones asignan permisos nuevos buscando el rol por este nombre
+    if es_rol_administrador(&actual.nombre) && !es_rol_administrador(&rol.nombre) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "El rol administrador no puede renombrarse"
+        }));
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<Rol, sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let r = sqlx::query_as::<_, Rol>(
+            "UPDATE rol SET nombre = $1, descripcion = $2 WHERE id_rol = $3
+             RETURNING id_rol, nombre, descripcion"
+        )
+        .bind(rol.nombre.trim())
+        .bind(&rol.descripcion)
+        .bind(id_rol)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let (Some(antes), Some(despues)) = (auditoria::a_json(&actual), auditoria::a_json(&r)) {
+            let (antes, despues) = auditoria::diferencias(&antes, &despues);
+            auditoria::registrar(&mut *tx, &ctx, ENTIDAD_ROL, Some(id_rol), "actualizar_rol", Some(antes), Some(despues)).await?;
+        }
+
+        tx.commit().await?;
+        Ok(r)
+    }
+    .await;
+
+    match resultado {
+        Ok(r) => HttpResponse::Ok().json(r),
+        Err(e) => error_rol(e, "Error al actualizar rol"),
+    }
+}
+
+// Solo se elimina si ningún usuario lo tiene asignado (409 en caso contrario)
+pub async fn eliminar_rol(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    id: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+    let id_rol = id.into_inner();
+
+    let resultado: Result<Option<(String, i64)>, sqlx::Error> = sqlx::query_as(
+        "SELECT r.nombre, (SELECT COUNT(*) FROM rol_usuario ru WHERE ru.id_rol = r.id_rol)
+         FROM rol r WHERE r.id_rol = $1"
+    )
+    .bind(id_rol)
+    .fetch_optional(&app_state.pool_pg)
+    .await;
+
+    let (nombre, asignados) = match resultado {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Rol no encontrado"
+            }));
+        }
+        Err(e) => return error_rol(e, "Error al obtener rol"),
+    };
+
+    if es_rol_administrador(&nombre) {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "El rol administrador no puede eliminarse"
+        }));
+    }
+    if asignados > 0 {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "El rol tiene usuarios asignados",
+            "usuarios_asignados": asignados
+        }));
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<(), sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        sqlx::query("DELETE FROM rol WHERE id_rol = $1")
+            .bind(id_rol)
+            .execute(&mut *tx)
+            .await?;
+
+        auditoria::registrar(
+            &mut *tx, &ctx, ENTIDAD_ROL, Some(id_rol), "eliminar_rol",
+            Some(serde_json::json!({ "id_rol": id_rol, "nombre": nombre })), None,
+        ).await?;
+
+        tx.commit().await
+    }
+    .await;
+
+    match resultado {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => error_rol(e, "Error al eliminar rol"),
+    }
+}
@@ -0,0 +1,33 @@
+// Normalización de texto compartida (logins, búsquedas por nombre)
+
+// Reemplaza vocales acentuadas, diéresis, ñ y ç por su letra base
+pub fn quitar_acentos(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            'á' | 'à' | 'ä' | 'â' | 'ã' => 'a',
+            'é' | 'è' | 'ë' | 'ê' => 'e',
+            'í' | 'ì' | 'ï' | 'î' => 'i',
+            'ó' | 'ò' | 'ö' | 'ô' | 'õ' => 'o',
+            'ú' | 'ù' | 'ü' | 'û' => 'u',
+            'ñ' => 'n',
+            'ç' => 'c',
+            'Á' | 'À' | 'Ä' | 'Â' | 'Ã' => 'A',
+            'É' | 'È' | 'Ë' | 'Ê' => 'E',
+            'Í' | 'Ì' | 'Ï' | 'Î' => 'I',
+            'Ó' | 'Ò' | 'Ö' | 'Ô' | 'Õ' => 'O',
+            'Ú' | 'Ù' | 'Ü' | 'Û' => 'U',
+            'Ñ' => 'N',
+            'Ç' => 'C',
+            otro => otro,
+        })
+        .collect()
+}
+
+// Mayúsculas, sin acentos y con espacios simples (para comparar nombres)
+pub fn normalizar(s: &str) -> String {
+    quitar_acentos(s)
+        .to_uppercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,11 +1,23 @@
-use actix_web::{web, HttpResponse, Responder};
-use sqlx::FromRow;
+use actix_multipart::Multipart;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
+use futures_util::StreamExt;
+use sqlx::{FromRow, PgConnection, PgPool, Postgres, QueryBuilder};
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
 use log;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::collections::{HashMap, HashSet};
+use crate::modules::auditoria::{self, Consulta, Contexto, ENTIDAD_USUARIO};
+use crate::modules::auth::{self, AuthUser};
+use crate::modules::ac::UsuarioAC;
+use crate::modules::importacion::{self, FilaArchivo};
+use crate::modules::paginacion::{self, Pagina};
+use crate::modules::registro::{RegistroElectoralRepo, RegistroResult};
+use crate::modules::{passwords, texto};
 use crate::structs::AppState;
 
-#[derive(FromRow, Serialize, Deserialize, Debug)]
+// DTO de lectura: nunca incluye el hash del password
+#[derive(FromRow, Serialize, Debug)]
 pub struct Usuario {
     pub id: i32,
     pub nacionalidad: String,
@@ -13,29 +25,29 @@ pub struct Usuario {
     pub nombre: String,
     pub apellido: String,
     pub login: String,
-    pub password: String,
     pub activo: i32,
     pub expired: i32,
-    pub id_rol: i32,
+    pub id_roles: Vec<i32>,
+    pub roles: Vec<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+// DTOs de escritura (solo entrada)
+#[derive(Deserialize, Debug)]
 pub struct UsuarioCreate {
     pub nacionalidad: String,
     pub cedula: i32,
     pub nombre: String,
     pub apellido: String,
-    pub id_rol: i32,
+    pub id_roles: Vec<i32>,
     pub activo: i32,
-    pub expired: i32,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize)]
 pub struct UsuarioUpdate {
     pub password: Option<String>,
     pub activo: i32,
     pub expired: i32,
-    pub id_rol: i32,
+    pub id_roles: Vec<i32>,
 }
 
 #[derive(Serialize)]
@@ -44,57 +56,166 @@ pub struct UsuarioConPassword {
     pub password_generada: String,
 }
 
-fn generar_login(nombre: &str, apellido: &str, _cedula: i32) -> String {
-    let inicial_nombre = nombre
+fn login_base(nombre: &str, apellido: &str) -> String {
+    let limpiar = |s: &str| -> String {
+        texto::quitar_acentos(s)
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect()
+    };
+
+    let inicial_nombre = limpiar(nombre)
         .chars()
         .next()
-        .map(|c| c.to_lowercase().to_string())
+        .map(String::from)
         .unwrap_or_default();
-    
-    let apellido_limpio = apellido.trim().to_lowercase();
-    
-    format!("{}{}", inicial_nombre, apellido_limpio)
+
+    let base = format!("{}{}", inicial_nombre, limpiar(apellido));
+    if base.is_empty() { "usuario".to_string() } else { base }
 }
 
-fn generar_password(nombre: &str, apellido: &str, cedula: i32) -> String {
-    let inicial_nombre = nombre
-        .chars()
-        .next()
-        .map(|c| c.to_lowercase().to_string())
-        .unwrap_or_default();
-    
-    let inicial_apellido = apellido
-        .chars()
-        .next()
-        .map(|c| c.to_lowercase().to_string())
-        .unwrap_or_default();
-    
-    format!("{}{}{}", inicial_nombre, inicial_apellido, cedula)
+// Primer login libre de: base, base + últimos 4 dígitos de la cédula, base + esos dígitos + 2, 3, ...
+async fn generar_login<'c, E>(executor: E, nombre: &str, apellido: &str, cedula: i32) -> Result<String, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    let base = login_base(nombre, apellido);
+
+    let ocupados: HashSet<String> = sqlx::query_scalar::<_, String>(
+        "SELECT login FROM usuario WHERE login LIKE $1 || '%'"
+    )
+    .bind(&base)
+    .fetch_all(executor)
+    .await?
+    .into_iter()
+    .collect();
+
+    if !ocupados.contains(&base) {
+        return Ok(base);
+    }
+
+    let con_cedula = format!("{}{:04}", base, cedula.rem_euclid(10_000));
+    if !ocupados.contains(&con_cedula) {
+        return Ok(con_cedula);
+    }
+
+    let login = (2..)
+        .map(|n| format!("{}{}", con_cedula, n))
+        .find(|l| !ocupados.contains(l))
+        .unwrap_or(con_cedula);
+    Ok(login)
 }
 
-pub async fn get_usuarios(
-    app_state: web::Data<AppState>,
-) -> impl Responder {
-    match sqlx::query_as::<_, Usuario>(
-        "SELECT 
+// Filtros, orden y paginación de GET /api/usuarios
+#[derive(Deserialize)]
+pub struct UsuariosQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub q: Option<String>, // búsqueda en login, nombre, apellido y cédula
+    pub activo: Option<i32>,
+    pub expired: Option<i32>,
+    pub id_rol: Option<i32>,
+    pub sort: Option<String>,
+    pub dir: Option<String>, // asc | desc
+}
+
+// Una fila por usuario; los roles se agregan en arreglos
+const SELECT_USUARIOS: &str = "SELECT 
             u.id, 
             u.nacionalidad, 
             u.cedula, 
             u.nombre, 
             u.apellido, 
             u.login, 
-            u.password, 
             u.activo, 
             u.expired,
-            COALESCE(ru.id_rol, 1) AS id_rol
-         FROM usuario u
-         LEFT JOIN rol_usuario ru ON u.id = ru.id_usuario
-         ORDER BY u.id DESC"
-    )
-    .fetch_all(&app_state.pool_pg)
-    .await
-    {
-        Ok(users) => HttpResponse::Ok().json(users),
+            ARRAY(SELECT ru.id_rol FROM rol_usuario ru WHERE ru.id_usuario = u.id ORDER BY ru.id_rol) AS id_roles,
+            ARRAY(SELECT r.nombre::TEXT FROM rol_usuario ru JOIN rol r ON r.id_rol = ru.id_rol
+                  WHERE ru.id_usuario = u.id ORDER BY r.id_rol) AS roles
+         FROM usuario u";
+
+fn escapar_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn filtros_usuarios(qb: &mut QueryBuilder<'_, Postgres>, f: &UsuariosQuery) {
+    qb.push(" WHERE 1=1");
+
+    if let Some(q) = f.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        let patron = format!("%{}%", escapar_like(q));
+        qb.push(" AND (u.login ILIKE ").push_bind(patron.clone());
+        qb.push(" OR u.nombre ILIKE ").push_bind(patron.clone());
+        qb.push(" OR u.apellido ILIKE ").push_bind(patron.clone());
+        qb.push(" OR CAST(u.cedula AS TEXT) LIKE ").push_bind(patron);
+        qb.push(")");
+    }
+    if let Some(activo) = f.activo {
+        qb.push(" AND u.activo = ").push_bind(activo);
+    }
+    if let Some(expired) = f.expired {
+        qb.push(" AND u.expired = ").push_bind(expired);
+    }
+    if let Some(id_rol) = f.id_rol {
+        qb.push(" AND EXISTS (SELECT 1 FROM rol_usuario f_ru WHERE f_ru.id_usuario = u.id AND f_ru.id_rol = ")
+            .push_bind(id_rol)
+            .push(")");
+    }
+}
+
+// Columna de orden siempre desde lista blanca
+fn orden_usuarios(f: &UsuariosQuery) -> String {
+    let columna = match f.sort.as_deref().map(str::trim) {
+        Some("login") => "u.login",
+        Some("nombre") => "u.nombre",
+        Some("apellido") => "u.apellido",
+        Some("cedula") => "u.cedula",
+        Some("nacionalidad") => "u.nacionalidad",
+        Some("activo") => "u.activo",
+        Some("expired") => "u.expired",
+        _ => "u.id",
+    };
+    let direccion = match f.dir.as_deref() {
+        Some(d) if d.trim().eq_ignore_ascii_case("asc") => "ASC",
+        _ => "DESC",
+    };
+    format!(" ORDER BY {} {}, u.id DESC", columna, direccion)
+}
+
+pub async fn get_usuarios(
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<UsuariosQuery>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    let (page, page_size, offset) = paginacion::normalizar(query.page, query.page_size, 20, 500);
+
+    let mut qb_total = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM usuario u");
+    filtros_usuarios(&mut qb_total, &query);
+
+    let mut qb_items = QueryBuilder::<Postgres>::new(SELECT_USUARIOS);
+    filtros_usuarios(&mut qb_items, &query);
+    qb_items.push(orden_usuarios(&query));
+    qb_items.push(" LIMIT ").push_bind(page_size);
+    qb_items.push(" OFFSET ").push_bind(offset);
+
+    let resultado = async {
+        let total = qb_total.build_query_scalar::<i64>().fetch_one(&app_state.pool_pg).await?;
+        let items = qb_items.build_query_as::<Usuario>().fetch_all(&app_state.pool_pg).await?;
+        Ok::<_, sqlx::Error>((total, items))
+    }
+    .await;
+
+    match resultado {
+        Ok((total, items)) => HttpResponse::Ok().json(Pagina {
+            items,
+            total,
+            page,
+            page_size,
+        }),
         Err(e) => {
             log::error!("Error al obtener usuarios: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
@@ -105,171 +226,527 @@ pub async fn get_usuarios(
     }
 }
 
-pub async fn get_roles(
+// =====================
+// Exportación (CSV / Excel) con los mismos filtros de get_usuarios
+// =====================
+
+#[derive(Deserialize)]
+pub struct ExportarQuery {
+    pub formato: Option<String>, // csv (defecto) | xlsx
+}
+
+const COLUMNAS_EXPORTACION: [&str; 10] = [
+    "id", "nacionalidad", "cedula", "nombre", "apellido", "login", "activo", "expired", "id_roles", "roles",
+];
+
+fn unir_roles(u: &Usuario) -> (String, String) {
+    let ids = u.id_roles.iter().map(|id| id.to_string()).collect::<Vec<_>>().join("|");
+    (ids, u.roles.join("|"))
+}
+
+fn usuarios_csv(usuarios: &[Usuario]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(COLUMNAS_EXPORTACION).map_err(|e| e.to_string())?;
+    for u in usuarios {
+        let (id_roles, roles) = unir_roles(u);
+        writer
+            .write_record([
+                u.id.to_string(),
+                u.nacionalidad.clone(),
+                u.cedula.to_string(),
+                u.nombre.clone(),
+                u.apellido.clone(),
+                u.login.clone(),
+                u.activo.to_string(),
+                u.expired.to_string(),
+                id_roles,
+                roles,
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+fn usuarios_xlsx(usuarios: &[Usuario]) -> Result<Vec<u8>, XlsxError> {
+    let mut libro = Workbook::new();
+    let negrita = Format::new().set_bold();
+    let hoja = libro.add_worksheet();
+    hoja.set_name("Usuarios")?;
+
+    for (col, titulo) in COLUMNAS_EXPORTACION.iter().enumerate() {
+        hoja.write_string_with_format(0, col as u16, *titulo, &negrita)?;
+    }
+
+    for (i, u) in usuarios.iter().enumerate() {
+        let fila = i as u32 + 1;
+        hoja.write_number(fila, 0, u.id)?;
+        hoja.write_string(fila, 1, &u.nacionalidad)?;
+        hoja.write_number(fila, 2, u.cedula)?;
+        hoja.write_string(fila, 3, &u.nombre)?;
+        hoja.write_string(fila, 4, &u.apellido)?;
+        hoja.write_string(fila, 5, &u.login)?;
+        hoja.write_number(fila, 6, u.activo)?;
+        hoja.write_number(fila, 7, u.expired)?;
+        let (id_roles, roles) = unir_roles(u);
+        hoja.write_string(fila, 8, id_roles)?;
+        hoja.write_string(fila, 9, roles)?;
+    }
+    hoja.autofit();
+
+    libro.save_to_buffer()
+}
+
+// GET /api/usuarios/exportar?formato=csv|xlsx&q=...&activo=...&sort=...
+pub async fn exportar_usuarios(
     app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<UsuariosQuery>,
+    exportar: web::Query<ExportarQuery>,
 ) -> impl Responder {
-    match sqlx::query_as::<_, (i32, String)>(
-        "SELECT id_rol, nombre FROM rol ORDER BY id_rol"
-    )
-    .fetch_all(&app_state.pool_pg)
-    .await
-    {
-        Ok(roles) => HttpResponse::Ok().json(roles),
+    // Es el mismo listado de get_usuarios: exportar solo no basta
+    for permiso in [auth::PERM_ADMINISTRAR_USUARIOS, auth::PERM_EXPORTAR] {
+        if let Err(e) = auth::require_permiso(&app_state, &user, permiso).await {
+            return e.error_response();
+        }
+    }
+
+    let xlsx = match exportar.formato.as_deref().map(|f| f.trim().to_lowercase()) {
+        None => false,
+        Some(f) if f == "csv" => false,
+        Some(f) if f == "xlsx" => true,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "formato debe ser csv o xlsx"
+            }));
+        }
+    };
+
+    let mut qb = QueryBuilder::<Postgres>::new(SELECT_USUARIOS);
+    filtros_usuarios(&mut qb, &query);
+    qb.push(orden_usuarios(&query));
+
+    let usuarios = match qb.build_query_as::<Usuario>().fetch_all(&app_state.pool_pg).await {
+        Ok(u) => u,
         Err(e) => {
-            log::error!("Error al obtener roles: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
+            log::error!("Error al exportar usuarios: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "Database error",
                 "details": e.to_string()
-            }))
+            }));
+        }
+    };
+
+    let (contenido, content_type, extension) = if xlsx {
+        (
+            usuarios_xlsx(&usuarios).map_err(|e| e.to_string()),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+        )
+    } else {
+        (usuarios_csv(&usuarios), "text/csv; charset=utf-8", "csv")
+    };
+
+    let contenido = match contenido {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Error generando archivo de usuarios: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Error generando archivo",
+                "details": e
+            }));
         }
+    };
+
+    let nombre = format!("usuarios_{}.{}", chrono::Local::now().format("%Y%m%d%H%M%S"), extension);
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(nombre)],
+        })
+        .body(contenido)
+}
+
+#[derive(Deserialize)]
+pub struct CrearUsuarioQuery {
+    // Cotejar nacionalidad + cédula contra RE.AC antes de crear
+    #[serde(default)]
+    pub validar_ac: bool,
+}
+
+enum CotejoAc {
+    Coincide,
+    NoExiste,
+    Discrepancia(UsuarioAC),
+}
+
+// El dato coincide con el primer nombre/apellido o con ambos (sin acentos ni mayúsculas)
+fn coincide_ac(dado: &str, primero: Option<&str>, segundo: Option<&str>) -> bool {
+    let dado = texto::normalizar(dado);
+    let primero = texto::normalizar(primero.unwrap_or_default());
+    let completo = texto::normalizar(&format!("{} {}", primero, segundo.unwrap_or_default()));
+    !dado.is_empty() && (dado == primero || dado == completo)
+}
+
+// Coteja contra el registro de RE.AC (misma consulta que get_usuario_by_ac).
+// Si nombre o apellido vienen vacíos se completan con los del registro.
+fn cotejar_ac(registro: Option<UsuarioAC>, usuario: &mut UsuarioCreate) -> CotejoAc {
+    let registro = match registro {
+        Some(r) => r,
+        None => return CotejoAc::NoExiste,
+    };
+
+    if usuario.nombre.trim().is_empty() {
+        usuario.nombre = registro.primer_nombre.clone().unwrap_or_default().trim().to_string();
+    }
+    if usuario.apellido.trim().is_empty() {
+        usuario.apellido = registro.primer_apellido.clone().unwrap_or_default().trim().to_string();
+    }
+
+    let nombre_ok = coincide_ac(&usuario.nombre, registro.primer_nombre.as_deref(), registro.segundo_nombre.as_deref());
+    let apellido_ok = coincide_ac(&usuario.apellido, registro.primer_apellido.as_deref(), registro.segundo_apellido.as_deref());
+
+    if nombre_ok && apellido_ok {
+        CotejoAc::Coincide
+    } else {
+        CotejoAc::Discrepancia(registro)
     }
 }
 
-pub async fn crear_usuario(
-    app_state: web::Data<AppState>,
-    usuario: web::Json<UsuarioCreate>,
-) -> impl Responder {
-    let login = generar_login(&usuario.nombre, &usuario.apellido, usuario.cedula);
-    let password_generada = generar_password(&usuario.nombre, &usuario.apellido, usuario.cedula);
-    let hashed_password = format!("{:x}", Sha256::digest(password_generada.as_bytes()));
+// Reemplaza el conjunto de roles del usuario, dentro de la transacción del llamador
+async fn asignar_roles(conn: &mut PgConnection, id_usuario: i32, id_roles: &[i32]) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM rol_usuario WHERE id_usuario = $1")
+        .bind(id_usuario)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO rol_usuario (id_rol, id_usuario)
+         SELECT DISTINCT UNNEST($1::int4[]), $2"
+    )
+    .bind(id_roles)
+    .bind(id_usuario)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn obtener_usuario(conn: &mut PgConnection, id_usuario: i32) -> Result<Option<Usuario>, sqlx::Error> {
+    sqlx::query_as::<_, Usuario>(&format!("{} WHERE u.id = $1", SELECT_USUARIOS))
+        .bind(id_usuario)
+        .fetch_optional(conn)
+        .await
+}
+
+fn sin_roles() -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Debe asignar al menos un rol"
+    }))
+}
 
-    let user = match sqlx::query_as::<_, Usuario>(
+// Inserta usuario + roles + registro de auditoría en una sola transacción
+async fn insertar_usuario(
+    pool: &PgPool,
+    ctx: &Contexto,
+    accion: &str,
+    usuario: &UsuarioCreate,
+    hashed_password: &str,
+) -> Result<Usuario, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let login = generar_login(&mut *tx, &usuario.nombre, &usuario.apellido, usuario.cedula).await?;
+
+    let id_usuario: i32 = sqlx::query_scalar(
         "INSERT INTO usuario (nacionalidad, cedula, nombre, apellido, login, password, activo, expired) 
          VALUES ($1, $2, $3, $4, $5, $6, $7, $8) 
-         RETURNING id, nacionalidad, cedula, nombre, apellido, login, password, activo, expired, 1 AS id_rol"
+         RETURNING id"
     )
     .bind(&usuario.nacionalidad)
     .bind(usuario.cedula)
     .bind(&usuario.nombre)
     .bind(&usuario.apellido)
     .bind(&login)
-    .bind(&hashed_password)
+    .bind(hashed_password)
     .bind(usuario.activo)
-    .bind(usuario.expired)
-    .fetch_one(&app_state.pool_pg)
-    .await
-    {
-        Ok(u) => u,
-        Err(e) => {
-            log::error!("Error al crear usuario: {}", e);
-            return HttpResponse::InternalServerError().json(serde_json::json!({
+    .bind(1) // expired: debe cambiar el password generado en el primer login
+    .fetch_one(&mut *tx)
+    .await?;
+
+    asignar_roles(&mut tx, id_usuario, &usuario.id_roles).await?;
+    let user = obtener_usuario(&mut tx, id_usuario).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+    auditoria::registrar(&mut *tx, ctx, ENTIDAD_USUARIO, Some(id_usuario), accion, None, auditoria::a_json(&user)).await?;
+
+    tx.commit().await?;
+    Ok(user)
+}
+
+fn error_escritura(e: sqlx::Error, contexto: &str) -> HttpResponse {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            log::warn!("{}: duplicado: {}", contexto, e);
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Ya existe un usuario con esa cédula o login",
+                "details": e.to_string()
+            }))
+        }
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            log::warn!("{}: referencia inválida: {}", contexto, e);
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Rol inexistente",
+                "details": e.to_string()
+            }))
+        }
+        _ => {
+            log::error!("{}: {}", contexto, e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "Database error",
                 "details": e.to_string()
+            }))
+        }
+    }
+}
+
+pub async fn crear_usuario(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<CrearUsuarioQuery>,
+    usuario: web::Json<UsuarioCreate>,
+) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    let mut usuario = usuario.into_inner();
+    usuario.nacionalidad = usuario.nacionalidad.trim().to_uppercase();
+    if usuario.id_roles.is_empty() {
+        return sin_roles();
+    }
+
+    let ctx = Contexto::new(&req, &user);
+
+    if query.validar_ac {
+        let registro = match app_state.registro.find_ac(&usuario.nacionalidad, i64::from(usuario.cedula)).await {
+            Ok(r) => r,
+            Err(e) => return e.error_response(),
+        };
+
+        // La respuesta puede incluir los nombres de AC: se registra como consulta
+        if let Err(e) = auditoria::registrar_consulta(&app_state, &ctx, auditoria::CONSULTA_AC, Consulta {
+            nacionalidad: Some(usuario.nacionalidad.clone()),
+            cedula: Some(i64::from(usuario.cedula)),
+            filtros: Some(serde_json::json!({ "origen": "crear_usuario" })),
+            resultados: usize::from(registro.is_some()),
+            ..Default::default()
+        }).await {
+            return e.error_response();
+        }
+
+        match cotejar_ac(registro, &mut usuario) {
+            CotejoAc::Coincide => {}
+            CotejoAc::NoExiste => {
+                return HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                    "error": "La cédula no existe en el registro AC"
+                }));
+            }
+            CotejoAc::Discrepancia(registro) => {
+                return HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                    "error": "Nombre o apellido no coinciden con el registro AC",
+                    "ac": registro
+                }));
+            }
+        }
+    }
+
+    let password_generada = passwords::generar_password_inicial();
+    let hashed_password = match passwords::hash_password_async(&password_generada).await {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("Error al generar hash de password: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Error al generar password"
             }));
         }
     };
 
-    if let Err(e) = sqlx::query(
-        "INSERT INTO rol_usuario (id_rol, id_usuario) VALUES ($1, $2)"
-    )
-    .bind(usuario.id_rol)
-    .bind(user.id)
-    .execute(&app_state.pool_pg)
-    .await
-    {
-        log::error!("Error al asignar rol: {}", e);
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Error al asignar rol",
-            "details": e.to_string()
-        }));
+    match insertar_usuario(&app_state.pool_pg, &ctx, "crear", &usuario, &hashed_password).await {
+        Ok(creado) => HttpResponse::Created().json(UsuarioConPassword {
+            usuario: creado,
+            password_generada,
+        }),
+        Err(e) => error_escritura(e, "Error al crear usuario"),
     }
-
-    let mut user_with_rol = user;
-    user_with_rol.id_rol = usuario.id_rol;
-    
-    HttpResponse::Created().json(UsuarioConPassword {
-        usuario: user_with_rol,
-        password_generada,
-    })
 }
 
 pub async fn actualizar_usuario(
+    req: HttpRequest,
     app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
     id: web::Path<i32>,
     usuario: web::Json<UsuarioUpdate>,
 ) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
     let user_id = id.into_inner();
+    if usuario.id_roles.is_empty() {
+        return sin_roles();
+    }
 
-    let existing_user = match sqlx::query_as::<_, Usuario>(
-        "SELECT id, nacionalidad, cedula, nombre, apellido, login, password, activo, expired, 
-                COALESCE((SELECT id_rol FROM rol_usuario WHERE id_usuario = $1), 1) AS id_rol
-         FROM usuario WHERE id = $1"
-    )
-    .bind(user_id)
-    .fetch_one(&app_state.pool_pg)
-    .await
-    {
-        Ok(u) => u,
-        Err(e) => {
-            log::error!("Usuario no encontrado: {}", e);
-            return HttpResponse::NotFound().json(serde_json::json!({
-                "error": "Usuario no encontrado"
+    let password_nuevo = usuario.password.as_deref().filter(|p| !p.trim().is_empty());
+
+    // Un restablecimiento por el administrador cumple la misma política que cuenta::cambiar_password
+    if let Some(p) = password_nuevo {
+        let cedula = match sqlx::query_scalar::<_, i32>("SELECT cedula FROM usuario WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&app_state.pool_pg)
+            .await
+        {
+            Ok(Some(c)) => c,
+            Ok(None) => {
+                return HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "Usuario no encontrado"
+                }));
+            }
+            Err(e) => {
+                log::error!("Error al obtener usuario {}: {}", user_id, e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Database error",
+                    "details": e.to_string()
+                }));
+            }
+        };
+        if let Err(errores) = passwords::validar_politica(p, cedula) {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "La contraseña no cumple la política",
+                "details": errores
             }));
         }
-    };
+    }
 
-    let password_to_use = match &usuario.password {
-        Some(p) if !p.trim().is_empty() => format!("{:x}", Sha256::digest(p.as_bytes())),
-        _ => existing_user.password,
+    let password_to_use = match password_nuevo {
+        Some(p) => match passwords::hash_password_async(p).await {
+            Ok(h) => Some(h),
+            Err(e) => {
+                log::error!("Error al generar hash de password: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Error al generar password"
+                }));
+            }
+        },
+        _ => None,
     };
 
-    let updated_user = match sqlx::query_as::<_, Usuario>(
-        "UPDATE usuario SET password = $1, activo = $2, expired = $3 WHERE id = $4 
-         RETURNING id, nacionalidad, cedula, nombre, apellido, login, password, activo, expired, $5 AS id_rol"
-    )
-    .bind(&password_to_use)
-    .bind(usuario.activo)
-    .bind(usuario.expired)
-    .bind(user_id)
-    .bind(usuario.id_rol)
-    .fetch_one(&app_state.pool_pg)
-    .await
-    {
-        Ok(u) => u,
-        Err(e) => {
-            log::error!("Error al actualizar usuario: {}", e);
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Error al actualizar usuario",
-                "details": e.to_string()
-            }));
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<Option<Usuario>, sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let anterior = match obtener_usuario(&mut tx, user_id).await? {
+            Some(u) => u,
+            None => return Ok(None),
+        };
+
+        // La contraseña que asigna el administrador es temporal: el usuario debe cambiarla al entrar
+        let expired = if password_to_use.is_some() {
+            sqlx::query("INSERT INTO historial_password (id_usuario, password) SELECT id, password FROM usuario WHERE id = $1")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            1
+        } else {
+            usuario.expired
+        };
+
+        sqlx::query(
+            "UPDATE usuario SET password = COALESCE($1, password), activo = $2, expired = $3 WHERE id = $4"
+        )
+        .bind(&password_to_use)
+        .bind(usuario.activo)
+        .bind(expired)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        asignar_roles(&mut tx, user_id, &usuario.id_roles).await?;
+        let updated_user = obtener_usuario(&mut tx, user_id).await?.ok_or(sqlx::Error::RowNotFound)?;
+
+        // El hash nunca se guarda en la bitácora, solo que hubo cambio
+        // (sin "password" en la clave: sin_secretos la descartaría)
+        if let (Some(antes), Some(despues)) = (auditoria::a_json(&anterior), auditoria::a_json(&updated_user)) {
+            let (antes, mut despues) = auditoria::diferencias(&antes, &despues);
+            if password_to_use.is_some() {
+                despues["credencial_restablecida"] = serde_json::Value::Bool(true);
+            }
+            auditoria::registrar(&mut *tx, &ctx, ENTIDAD_USUARIO, Some(user_id), "actualizar", Some(antes), Some(despues)).await?;
         }
-    };
 
-    if let Err(e) = sqlx::query(
-        "UPDATE rol_usuario SET id_rol = $1 WHERE id_usuario = $2"
-    )
-    .bind(usuario.id_rol)
-    .bind(user_id)
-    .execute(&app_state.pool_pg)
-    .await
-    {
-        log::error!("Error al actualizar rol: {}", e);
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Error al actualizar rol",
-            "details": e.to_string()
-        }));
+        tx.commit().await?;
+        Ok(Some(updated_user))
     }
+    .await;
 
-    HttpResponse::Ok().json(updated_user)
+    match resultado {
+        Ok(Some(u)) => HttpResponse::Ok().json(u),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Usuario no encontrado"
+        })),
+        Err(e) => error_escritura(e, "Error al actualizar usuario"),
+    }
 }
 
 pub async fn bloquear_usuario(
+    req: HttpRequest,
     app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
     id: web::Path<i32>,
 ) -> impl Responder {
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
     let user_id = id.into_inner();
 
-    match sqlx::query_as::<_, Usuario>(
-        "UPDATE usuario SET activo = CASE WHEN activo = 1 THEN 0 ELSE 1 END 
-         WHERE id = $1 
-         RETURNING id, nacionalidad, cedula, nombre, apellido, login, password, activo, expired, 
-                  COALESCE((SELECT id_rol FROM rol_usuario WHERE id_usuario = $1), 1) AS id_rol"
-    )
-    .bind(user_id)
-    .fetch_one(&app_state.pool_pg)
-    .await
-    {
-        Ok(user) => HttpResponse::Ok().json(user),
+    let ctx = Contexto::new(&req, &user);
+
+    let resultado: Result<Option<Usuario>, sqlx::Error> = async {
+        let mut tx = app_state.pool_pg.begin().await?;
+
+        let activo: Option<i32> = sqlx::query_scalar(
+            "UPDATE usuario SET activo = CASE WHEN activo = 1 THEN 0 ELSE 1 END 
+             WHERE id = $1
+             RETURNING activo"
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let activo = match activo {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+
+        auditoria::registrar(
+            &mut *tx, &ctx, ENTIDAD_USUARIO, Some(user_id),
+            if activo == 1 { "desbloquear" } else { "bloquear" },
+            Some(serde_json::json!({ "activo": 1 - activo })),
+            Some(serde_json::json!({ "activo": activo })),
+        ).await?;
+
+        let actualizado = obtener_usuario(&mut tx, user_id).await?;
+        tx.commit().await?;
+        Ok(actualizado)
+    }
+    .await;
+
+    match resultado {
+        Ok(Some(user)) => HttpResponse::Ok().json(user),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Usuario no encontrado"
+        })),
         Err(e) => {
             log::error!("Error al bloquear usuario: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
@@ -280,9 +757,466 @@ pub async fn bloquear_usuario(
     }
 }
 
+// =====================
+// Carga masiva (CSV / Excel)
+// =====================
+
+const TAMANO_MAXIMO_CARGA: usize = 5 * 1024 * 1024;
+// Cada fila creada es un hash Argon2 y una transacción dentro de la misma petición
+const FILAS_MAXIMAS_CARGA: usize = 2000;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EstadoFila {
+    Valido, // solo en dry_run
+    Creado,
+    Duplicado,
+    Invalido,
+    Discrepancia, // nombre/apellido distintos a RE.AC (validar_ac)
+    Error,
+}
+
+#[derive(Serialize)]
+pub struct ResultadoFila {
+    pub fila: usize,
+    pub cedula: String,
+    pub estado: EstadoFila,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mensaje: Option<String>,
+    // Solo si no se pudo generar archivo_credenciales: el password va en la fila
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+// CSV con los passwords generados; se entrega una sola vez en la respuesta
+#[derive(Serialize)]
+pub struct ArchivoCredenciales {
+    pub nombre: String,
+    pub contenido: String,
+}
+
+#[derive(Deserialize)]
+pub struct CargaMasivaQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub validar_ac: bool,
+}
+
+#[derive(Serialize)]
+pub struct ReporteCarga {
+    pub dry_run: bool,
+    pub total: usize,
+    pub validos: usize,
+    pub creados: usize,
+    pub duplicados: usize,
+    pub invalidos: usize,
+    pub discrepancias: usize,
+    pub errores: usize,
+    pub filas: Vec<ResultadoFila>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archivo_credenciales: Option<ArchivoCredenciales>,
+}
+
+impl ReporteCarga {
+    fn new(dry_run: bool, filas: Vec<ResultadoFila>, archivo_credenciales: Option<ArchivoCredenciales>) -> Self {
+        let contar = |estado: EstadoFila| filas.iter().filter(|f| f.estado == estado).count();
+        ReporteCarga {
+            dry_run,
+            total: filas.len(),
+            validos: contar(EstadoFila::Valido),
+            creados: contar(EstadoFila::Creado),
+            duplicados: contar(EstadoFila::Duplicado),
+            invalidos: contar(EstadoFila::Invalido),
+            discrepancias: contar(EstadoFila::Discrepancia),
+            errores: contar(EstadoFila::Error),
+            archivo_credenciales,
+            filas,
+        }
+    }
+}
+
+// Con nombres_obligatorios = false (validar_ac) nombre y apellido pueden venir vacíos
+fn validar_fila(f: &FilaArchivo, nombres_obligatorios: bool) -> Result<UsuarioCreate, String> {
+    let nacionalidad = f.nacionalidad.trim().to_uppercase();
+    if !(nacionalidad == "V" || nacionalidad == "E") {
+        return Err("nacionalidad debe ser V o E".to_string());
+    }
+
+    let cedula: i32 = f.cedula.trim().parse().map_err(|_| "cedula inválida".to_string())?;
+    if cedula <= 0 || cedula > 99_999_999 {
+        return Err("cedula inválida".to_string());
+    }
+
+    let nombre = f.nombre.trim();
+    let apellido = f.apellido.trim();
+    if nombres_obligatorios && (nombre.is_empty() || apellido.is_empty()) {
+        return Err("nombre y apellido son obligatorios".to_string());
+    }
+
+    // Varios roles separados por '|': "1|3"
+    let id_roles = f
+        .id_rol
+        .split('|')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|r| r.parse::<i32>())
+        .collect::<Result<Vec<i32>, _>>()
+        .map_err(|_| "id_rol inválido".to_string())?;
+    if id_roles.is_empty() {
+        return Err("id_rol es obligatorio".to_string());
+    }
+
+    Ok(UsuarioCreate {
+        nacionalidad,
+        cedula,
+        nombre: nombre.to_string(),
+        apellido: apellido.to_string(),
+        id_roles,
+        activo: 1,
+    })
+}
+
+// Valida todas las filas sin escribir en la BD: formato, rol existente,
+// cédulas repetidas dentro del archivo y cédulas ya registradas en usuario.
+// Devuelve cada resultado junto con el usuario a crear si la fila es válida.
+async fn validar_carga(
+    pool: &PgPool,
+    filas: &[FilaArchivo],
+    validar_ac: bool,
+) -> Result<Vec<(ResultadoFila, Option<UsuarioCreate>)>, sqlx::Error> {
+    let roles: HashSet<i32> = sqlx::query_scalar::<_, i32>("SELECT id_rol FROM rol")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut validadas: Vec<(ResultadoFila, Option<UsuarioCreate>)> = filas
+        .iter()
+        .map(|f| {
+            let resultado = ResultadoFila {
+                fila: f.fila,
+                cedula: f.cedula.clone(),
+                estado: EstadoFila::Valido,
+                login: None,
+                mensaje: None,
+                password: None,
+            };
+            (resultado, validar_fila(f, !validar_ac))
+        })
+        .map(|(mut resultado, validacion)| match validacion {
+            Ok(u) => match u.id_roles.iter().find(|id| !roles.contains(id)) {
+                None => (resultado, Some(u)),
+                Some(id) => {
+                    resultado.estado = EstadoFila::Invalido;
+                    resultado.mensaje = Some(format!("El rol {} no existe", id));
+                    (resultado, None)
+                }
+            },
+            Err(e) => {
+                resultado.estado = EstadoFila::Invalido;
+                resultado.mensaje = Some(e);
+                (resultado, None)
+            }
+        })
+        .collect();
+
+    let cedulas: Vec<i32> = validadas.iter().filter_map(|(_, u)| u.as_ref().map(|u| u.cedula)).collect();
+    let registradas: HashSet<i32> = sqlx::query_scalar::<_, i32>("SELECT cedula FROM usuario WHERE cedula = ANY($1)")
+        .bind(&cedulas)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut vistas: HashMap<i32, usize> = HashMap::new();
+    for (resultado, usuario) in validadas.iter_mut() {
+        let cedula = match usuario {
+            Some(u) => u.cedula,
+            None => continue,
+        };
+
+        let mensaje = if registradas.contains(&cedula) {
+            "Ya existe un usuario con esa cédula".to_string()
+        } else if let Some(fila) = vistas.get(&cedula) {
+            format!("Cédula repetida en el archivo (fila {})", fila)
+        } else {
+            vistas.insert(cedula, resultado.fila);
+            continue;
+        };
+
+        resultado.estado = EstadoFila::Duplicado;
+        resultado.mensaje = Some(mensaje);
+        *usuario = None;
+    }
+
+    Ok(validadas)
+}
+
+// Coteja contra RE.AC las filas válidas; las que no coinciden se marcan y no se crean.
+// Devuelve las cédulas encontradas en AC (para la bitácora de consultas).
+async fn cotejar_carga(
+    repo: &dyn RegistroElectoralRepo,
+    validadas: &mut [(ResultadoFila, Option<UsuarioCreate>)],
+) -> RegistroResult<Vec<i64>> {
+    let cedulas: Vec<(String, i64)> = validadas
+        .iter()
+        .filter_map(|(_, u)| u.as_ref().map(|u| (u.nacionalidad.clone(), i64::from(u.cedula))))
+        .collect();
+    let mut registros = repo.find_ac_lote(&cedulas).await?.into_iter();
+    let mut encontradas = Vec::new();
+
+    for (resultado, usuario) in validadas.iter_mut() {
+        let u = match usuario {
+            Some(u) => u,
+            None => continue,
+        };
+
+        let registro = registros.next().flatten();
+        if let Some(r) = &registro {
+            encontradas.push(r.cedula);
+        }
+
+        match cotejar_ac(registro, u) {
+            CotejoAc::Coincide => {}
+            CotejoAc::NoExiste => {
+                resultado.estado = EstadoFila::Invalido;
+                resultado.mensaje = Some("La cédula no existe en el registro AC".to_string());
+                *usuario = None;
+            }
+            CotejoAc::Discrepancia(registro) => {
+                let nombres = |a: Option<String>, b: Option<String>| {
+                    format!("{} {}", a.unwrap_or_default(), b.unwrap_or_default()).trim().to_string()
+                };
+                resultado.estado = EstadoFila::Discrepancia;
+                resultado.mensaje = Some(format!(
+                    "En AC: {} / {}",
+                    nombres(registro.primer_nombre, registro.segundo_nombre),
+                    nombres(registro.primer_apellido, registro.segundo_apellido)
+                ));
+                *usuario = None;
+            }
+        }
+    }
+
+    Ok(encontradas)
+}
+
+// Lee el primer campo con archivo del multipart: (nombre_archivo, contenido)
+async fn leer_multipart(payload: &mut Multipart) -> Result<(String, Vec<u8>), HttpResponse> {
+    while let Some(item) = payload.next().await {
+        let mut field = item.map_err(|e| {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Multipart inválido",
+                "details": e.to_string()
+            }))
+        })?;
+
+        let nombre_archivo = match field.content_disposition().and_then(|cd| cd.get_filename()) {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
+
+        let mut datos = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(|e| {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Error leyendo archivo",
+                    "details": e.to_string()
+                }))
+            })?;
+            if datos.len() + chunk.len() > TAMANO_MAXIMO_CARGA {
+                return Err(HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                    "error": format!("El archivo supera {} MB", TAMANO_MAXIMO_CARGA / 1024 / 1024)
+                })));
+            }
+            datos.extend_from_slice(&chunk);
+        }
+
+        return Ok((nombre_archivo, datos));
+    }
+
+    Err(HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "No se recibió ningún archivo"
+    })))
+}
+
+fn archivo_credenciales(creados: &[(Usuario, String)]) -> Result<ArchivoCredenciales, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["nacionalidad", "cedula", "nombre", "apellido", "login", "password"])
+        .map_err(|e| e.to_string())?;
+    for (u, password) in creados {
+        writer
+            .write_record([
+                u.nacionalidad.as_str(),
+                &u.cedula.to_string(),
+                u.nombre.as_str(),
+                u.apellido.as_str(),
+                u.login.as_str(),
+                password.as_str(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+
+    Ok(ArchivoCredenciales {
+        nombre: format!("credenciales_{}.csv", chrono::Local::now().format("%Y%m%d%H%M%S")),
+        contenido: String::from_utf8(bytes).map_err(|e| e.to_string())?,
+    })
+}
+
+// POST /api/usuarios/carga-masiva[?dry_run=true][&validar_ac=true] (multipart, archivo .csv / .xlsx)
+// Columnas: nacionalidad, cedula, nombre, apellido, id_rol (varios separados por '|')
+// Con dry_run=true solo valida y devuelve el reporte, sin escribir en la BD.
+// Con validar_ac=true cada fila se coteja contra RE.AC (nombre/apellido vacíos se completan).
+// Máximo FILAS_MAXIMAS_CARGA filas de datos (413 si se supera).
 pub async fn carga_masiva(
-    _app_state: web::Data<AppState>,
-    _payload: actix_multipart::Multipart,
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    user: web::ReqData<AuthUser>,
+    query: web::Query<CargaMasivaQuery>,
+    mut payload: Multipart,
 ) -> impl Responder {
-    HttpResponse::NotImplemented().body("Carga masiva deshabilitada temporalmente")
-}
\ No newline at end of file
+    if let Err(e) = auth::require_permiso(&app_state, &user, auth::PERM_ADMINISTRAR_USUARIOS).await {
+        return e.error_response();
+    }
+
+    let (nombre_archivo, datos) = match leer_multipart(&mut payload).await {
+        Ok(a) => a,
+        Err(resp) => return resp,
+    };
+
+    let formato = match importacion::detectar_formato(&nombre_archivo) {
+        Some(f) => f,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Formato no soportado (use .csv o .xlsx)"
+            }));
+        }
+    };
+
+    let filas = match importacion::leer_archivo(&datos, formato) {
+        Ok(f) => f,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Archivo inválido",
+                "details": e
+            }));
+        }
+    };
+
+    if filas.len() > FILAS_MAXIMAS_CARGA {
+        return HttpResponse::PayloadTooLarge().json(serde_json::json!({
+            "error": format!("El archivo supera el máximo de {} filas", FILAS_MAXIMAS_CARGA),
+            "details": format!("{} filas", filas.len())
+        }));
+    }
+
+    let mut validadas = match validar_carga(&app_state.pool_pg, &filas, query.validar_ac).await {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("Error validando carga masiva: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error",
+                "details": e.to_string()
+            }));
+        }
+    };
+
+    let ctx = Contexto::new(&req, &user);
+
+    if query.validar_ac {
+        let consultadas = validadas.iter().filter(|(_, u)| u.is_some()).count();
+        let encontradas = match cotejar_carga(app_state.registro.as_ref(), &mut validadas).await {
+            Ok(c) => c,
+            Err(e) => return e.error_response(),
+        };
+
+        // El reporte (también en dry_run) muestra los nombres de AC de las discrepancias
+        if let Err(e) = auditoria::registrar_consulta(&app_state, &ctx, auditoria::CONSULTA_AC, Consulta {
+            filtros: Some(serde_json::json!({ "origen": "carga_masiva", "cedulas_consultadas": consultadas })),
+            resultados: encontradas.len(),
+            cedulas_resultado: Some(encontradas),
+            ..Default::default()
+        }).await {
+            return e.error_response();
+        }
+    }
+
+    if query.dry_run {
+        let resultados = validadas.into_iter().map(|(r, _)| r).collect();
+        return HttpResponse::Ok().json(ReporteCarga::new(true, resultados, None));
+    }
+
+    let mut resultados = Vec::with_capacity(validadas.len());
+    let mut creados: Vec<(Usuario, String)> = Vec::new();
+
+    for (mut resultado, nuevo) in validadas {
+        let nuevo = match nuevo {
+            Some(u) => u,
+            None => {
+                resultados.push(resultado);
+                continue;
+            }
+        };
+
+        let password_generada = passwords::generar_password_inicial();
+        let hashed_password = match passwords::hash_password_async(&password_generada).await {
+            Ok(h) => h,
+            Err(e) => {
+                log::error!("Error al generar hash de password: {}", e);
+                resultado.estado = EstadoFila::Error;
+                resultado.mensaje = Some("Error al generar password".to_string());
+                resultados.push(resultado);
+                continue;
+            }
+        };
+
+        match insertar_usuario(&app_state.pool_pg, &ctx, "crear_carga_masiva", &nuevo, &hashed_password).await {
+            Ok(u) => {
+                resultado.estado = EstadoFila::Creado;
+                resultado.login = Some(u.login.clone());
+                creados.push((u, password_generada));
+            }
+            Err(sqlx::Error::Database(db)) if db.is_unique_violation() => {
+                resultado.estado = EstadoFila::Duplicado;
+                resultado.mensaje = Some("Ya existe un usuario con esa cédula o login".to_string());
+            }
+            Err(sqlx::Error::Database(db)) if db.is_foreign_key_violation() => {
+                resultado.estado = EstadoFila::Invalido;
+                resultado.mensaje = Some("Rol inexistente".to_string());
+            }
+            Err(e) => {
+                log::error!("Carga masiva, fila {}: {}", resultado.fila, e);
+                resultado.estado = EstadoFila::Error;
+                resultado.mensaje = Some(e.to_string());
+            }
+        }
+        resultados.push(resultado);
+    }
+
+    let credenciales = if creados.is_empty() {
+        None
+    } else {
+        match archivo_credenciales(&creados) {
+            Ok(a) => Some(a),
+            Err(e) => {
+                // Los usuarios ya existen: sin los passwords el administrador no podría entregarlos
+                log::error!("Error generando archivo de credenciales: {}", e);
+                for resultado in resultados.iter_mut() {
+                    resultado.password = creados
+                        .iter()
+                        .find(|(u, _)| Some(&u.login) == resultado.login.as_ref())
+                        .map(|(_, p)| p.clone());
+                }
+                None
+            }
+        }
+    };
+
+    log::info!("Carga masiva '{}': {} filas, {} creados", nombre_archivo, resultados.len(), creados.len());
+    HttpResponse::Ok().json(ReporteCarga::new(false, resultados, credenciales))
+}